@@ -4,20 +4,28 @@ use crate::downloader::DownloadConfig;
 pub struct CikLookupDataSources {
     pub lookup_ds: DataSource,
     pub tickers_exchange_ds: DataSource,
+    pub tickers_ds: DataSource,
+    pub tickers_mf_ds: DataSource,
 }
 
 impl CikLookupDataSources {
-    const LOOKUP_URL: &'static str = "https://www.sec.gov/Archives/edgar/cik-lookup-data.txt";
-    const TICKERS_EXCHANGE_URL: &'static str =
+    pub const LOOKUP_URL: &'static str = "https://www.sec.gov/Archives/edgar/cik-lookup-data.txt";
+    pub const TICKERS_EXCHANGE_URL: &'static str =
         "https://www.sec.gov/files/company_tickers_exchange.json";
+    pub const TICKERS_URL: &'static str = "https://www.sec.gov/files/company_tickers.json";
+    pub const TICKERS_MF_URL: &'static str = "https://www.sec.gov/files/company_tickers_mf.json";
 
     pub fn new(download_config: &DownloadConfig) -> Result<Self, DataSourceError> {
         let lookup_ds = DataSource::new(download_config, Self::LOOKUP_URL)?;
         let tickers_exchange_ds = DataSource::new(download_config, Self::TICKERS_EXCHANGE_URL)?;
+        let tickers_ds = DataSource::new(download_config, Self::TICKERS_URL)?;
+        let tickers_mf_ds = DataSource::new(download_config, Self::TICKERS_MF_URL)?;
 
         Ok(Self {
             lookup_ds,
             tickers_exchange_ds,
+            tickers_ds,
+            tickers_mf_ds,
         })
     }
 }
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use super::index::CikIndex;
+use super::record::CikEntity;
+use super::tickers::Listing;
+
+/// A change between two snapshots of the CIK lookup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum CikChange {
+    /// A CIK that was not in the older snapshot
+    Added { cik: usize, name: String },
+    /// A name not previously known for an existing CIK
+    NewName { cik: usize, name: String },
+    /// A ticker not previously listed for the CIK
+    Listed { listing: Listing },
+    /// A ticker that moved to another exchange. A missing exchange in either snapshot is not
+    /// reported as a move.
+    ExchangeChanged {
+        cik: usize,
+        ticker: String,
+        from: String,
+        to: String,
+    },
+    /// A ticker no longer listed for the CIK
+    Delisted { listing: Listing },
+}
+
+/// Changes from `old` to `new`, ordered as the entities of `new`, followed by the delistings
+/// of CIKs missing from `new`
+pub fn diff<'a>(old: &'a CikIndex, new: &'a CikIndex) -> impl Iterator<Item = CikChange> + 'a {
+    let changed = new
+        .entities()
+        .iter()
+        .flat_map(|entity| entity_changes(old.get(entity.cik), entity));
+
+    let removed = old
+        .entities()
+        .iter()
+        .filter(|entity| new.get(entity.cik).is_none())
+        .flat_map(|entity| &entity.listings)
+        .map(|listing| CikChange::Delisted {
+            listing: listing.clone(),
+        });
+
+    changed.chain(removed)
+}
+
+fn entity_changes(old: Option<&CikEntity>, new: &CikEntity) -> Vec<CikChange> {
+    let mut changes = vec![];
+    let cik = new.cik;
+
+    match old {
+        None => changes.push(CikChange::Added {
+            cik,
+            name: new.name().to_string(),
+        }),
+        Some(old) => {
+            let mut known = old
+                .names
+                .iter()
+                .chain(&old.current_name)
+                .collect::<HashSet<_>>();
+            let new_names = new
+                .names
+                .iter()
+                .chain(&new.current_name)
+                .filter(|name| known.insert(name))
+                .collect::<Vec<_>>();
+
+            changes.extend(new_names.into_iter().map(|name| CikChange::NewName {
+                cik,
+                name: name.to_string(),
+            }));
+        }
+    }
+
+    let old_listings = old.map(|e| e.listings.as_slice()).unwrap_or_default();
+    for listing in &new.listings {
+        match old_listings.iter().find(|l| l.ticker == listing.ticker) {
+            None => changes.push(CikChange::Listed {
+                listing: listing.clone(),
+            }),
+            Some(old)
+                if !old.exchange.is_empty()
+                    && !listing.exchange.is_empty()
+                    && old.exchange != listing.exchange =>
+            {
+                changes.push(CikChange::ExchangeChanged {
+                    cik,
+                    ticker: listing.ticker.to_string(),
+                    from: old.exchange.to_string(),
+                    to: listing.exchange.to_string(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let delisted = old_listings
+        .iter()
+        .filter(|old| !new.listings.iter().any(|l| l.ticker == old.ticker))
+        .map(|listing| CikChange::Delisted {
+            listing: listing.clone(),
+        });
+    changes.extend(delisted);
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cik_lookup::record::test_entity;
+
+    use super::*;
+
+    #[test]
+    fn it_diffs_snapshots() {
+        let old = CikIndex::from(vec![
+            test_entity(1, &["FACEBOOK INC"], None, &[("FB", "Nasdaq")]),
+            test_entity(
+                2,
+                &["ACME CORP"],
+                None,
+                &[("ACME", "NYSE"), ("ACME-P", "NYSE")],
+            ),
+            test_entity(3, &["GONE INC"], None, &[("GONE", "NYSE")]),
+        ]);
+        let new = CikIndex::from(vec![
+            test_entity(
+                1,
+                &["FACEBOOK INC", "META PLATFORMS INC"],
+                None,
+                &[("META", "Nasdaq")],
+            ),
+            test_entity(2, &["ACME CORP"], None, &[("ACME", "Nasdaq")]),
+            test_entity(4, &["NEWCO INC"], None, &[]),
+        ]);
+
+        let listing = |cik: usize, ticker: &str, exchange: &str| Listing {
+            cik,
+            ticker: ticker.to_string(),
+            exchange: exchange.to_string(),
+        };
+
+        assert_eq!(
+            diff(&old, &new).collect::<Vec<_>>(),
+            vec![
+                CikChange::NewName {
+                    cik: 1,
+                    name: "META PLATFORMS INC".to_string()
+                },
+                CikChange::Listed {
+                    listing: listing(1, "META", "Nasdaq")
+                },
+                CikChange::Delisted {
+                    listing: listing(1, "FB", "Nasdaq")
+                },
+                CikChange::ExchangeChanged {
+                    cik: 2,
+                    ticker: "ACME".to_string(),
+                    from: "NYSE".to_string(),
+                    to: "Nasdaq".to_string()
+                },
+                CikChange::Delisted {
+                    listing: listing(2, "ACME-P", "NYSE")
+                },
+                CikChange::Added {
+                    cik: 4,
+                    name: "NEWCO INC".to_string()
+                },
+                CikChange::Delisted {
+                    listing: listing(3, "GONE", "NYSE")
+                },
+            ]
+        );
+        assert_eq!(diff(&new, &new).count(), 0);
+
+        let unknown_exchange =
+            CikIndex::from(vec![test_entity(2, &["ACME CORP"], None, &[("ACME", "")])]);
+        let known_exchange = CikIndex::from(vec![test_entity(
+            2,
+            &["ACME CORP"],
+            None,
+            &[("ACME", "NYSE")],
+        )]);
+        assert_eq!(diff(&unknown_exchange, &known_exchange).count(), 0);
+        assert_eq!(diff(&known_exchange, &unknown_exchange).count(), 0);
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use snafu::{Location, ResultExt, Snafu};
+
+use super::index::{normalize_name, CikIndex};
+use super::record::CikEntity;
+
+/// Share class and security type words that are not part of a company name
+const SECURITY_WORDS: [&str; 9] = [
+    "CLASS", "CL", "COMMON", "STOCK", "SHARES", "ORDINARY", "ADR", "ADS", "SERIES",
+];
+
+#[derive(Debug, Snafu)]
+pub enum FuzzyError {
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to read or write {filepath:?}"))]
+    Csv {
+        source: csv::Error,
+        filepath: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Confidence {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 0.9 => Self::High,
+            s if s >= 0.75 => Self::Medium,
+            s if s >= 0.5 => Self::Low,
+            _ => Self::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzyMatch<'a> {
+    pub entity: &'a CikEntity,
+    /// Normalized name that matched
+    pub matched_name: String,
+    /// Similarity between 0 and 1
+    pub score: f64,
+}
+
+impl FuzzyMatch<'_> {
+    pub fn confidence(&self) -> Confidence {
+        Confidence::from_score(self.score)
+    }
+}
+
+/// A word with its character trigrams
+struct Token {
+    text: String,
+    trigrams: HashSet<String>,
+}
+
+impl Token {
+    fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            trigrams: trigrams(text),
+        }
+    }
+}
+
+struct Candidate {
+    name: String,
+    /// Indexes in `FuzzyMatcher::tokens`
+    tokens: Vec<usize>,
+    trigrams: HashSet<String>,
+    entity: usize,
+}
+
+/// Ranks entities of a `CikIndex` by similarity of their current and former names.
+///
+/// The score averages a token similarity, where each word counts by its closest word in the
+/// other name, and the Dice coefficient of character trigrams, so that both reordered words
+/// and typos are tolerated.
+///
+/// Candidates must share the first three letters of a word with the query. Words whose
+/// prefix is shared by more than `max_block_size` names, e.g. `FIRST` or `AMERICAN`, are
+/// only used when the query has no rarer word.
+pub struct FuzzyMatcher<'a> {
+    pub max_block_size: usize,
+    index: &'a CikIndex,
+    candidates: Vec<Candidate>,
+    tokens: Vec<Token>,
+    token_ids: HashMap<String, usize>,
+    by_token_prefix: HashMap<String, Vec<usize>>,
+}
+
+impl<'a> FuzzyMatcher<'a> {
+    pub const DEFAULT_LIMIT: usize = 5;
+    pub const DEFAULT_MAX_BLOCK_SIZE: usize = 1_000;
+
+    pub fn new(index: &'a CikIndex) -> Self {
+        let mut matcher = Self {
+            max_block_size: Self::DEFAULT_MAX_BLOCK_SIZE,
+            index,
+            candidates: vec![],
+            tokens: vec![],
+            token_ids: HashMap::new(),
+            by_token_prefix: HashMap::new(),
+        };
+
+        for (i, entity) in index.entities().iter().enumerate() {
+            let names = entity
+                .names
+                .iter()
+                .chain(&entity.current_name)
+                .map(|name| normalize_name(name))
+                .collect::<HashSet<_>>();
+
+            for name in names {
+                matcher.add_candidate(name, i);
+            }
+        }
+
+        matcher
+    }
+
+    fn add_candidate(&mut self, name: String, entity: usize) {
+        let c = self.candidates.len();
+        let words = words(&name);
+        for prefix in words
+            .iter()
+            .map(|t| token_prefix(t))
+            .collect::<HashSet<_>>()
+        {
+            self.by_token_prefix.entry(prefix).or_default().push(c);
+        }
+
+        let tokens = words.iter().map(|word| self.token_id(word)).collect();
+        self.candidates.push(Candidate {
+            trigrams: trigrams(&name),
+            tokens,
+            name,
+            entity,
+        });
+    }
+
+    /// Index of the word in `tokens`, computing its trigrams the first time it is seen
+    fn token_id(&mut self, word: &str) -> usize {
+        if let Some(id) = self.token_ids.get(word) {
+            return *id;
+        }
+
+        let id = self.tokens.len();
+        self.tokens.push(Token::new(word));
+        self.token_ids.insert(word.to_string(), id);
+
+        id
+    }
+
+    /// Candidates sharing a word prefix with the query, skipping blocks larger than
+    /// `max_block_size` unless no smaller block exists, in which case only the smallest is used
+    fn candidates(&self, words: &HashSet<String>) -> HashSet<usize> {
+        let mut blocks = words
+            .iter()
+            .filter_map(|t| self.by_token_prefix.get(&token_prefix(t)))
+            .collect::<Vec<_>>();
+        blocks.sort_by_key(|block| block.len());
+
+        let rare = blocks
+            .iter()
+            .take_while(|block| block.len() <= self.max_block_size)
+            .count();
+
+        blocks
+            .into_iter()
+            .take(rare.max(1))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Up to `limit` entities ordered by descending score, one match per entity
+    pub fn search(&self, name: &str, limit: usize) -> Vec<FuzzyMatch<'a>> {
+        let query = normalize_query(name);
+        let query_words = words(&query);
+        let query_tokens = query_words
+            .iter()
+            .map(|word| Token::new(word))
+            .collect::<Vec<_>>();
+        let query_tokens = query_tokens.iter().collect::<Vec<_>>();
+        let query_trigrams = trigrams(&query);
+
+        let mut best: HashMap<usize, (f64, &Candidate)> = HashMap::new();
+        for c in self.candidates(&query_words) {
+            let candidate = &self.candidates[c];
+            let candidate_tokens = candidate
+                .tokens
+                .iter()
+                .map(|id| &self.tokens[*id])
+                .collect::<Vec<_>>();
+            let score = (token_similarity(&query_tokens, &candidate_tokens)
+                + dice(&query_trigrams, &candidate.trigrams))
+                / 2.0;
+
+            let current = best.entry(candidate.entity).or_insert((score, candidate));
+            if score > current.0 {
+                *current = (score, candidate);
+            }
+        }
+
+        let mut result = best
+            .into_iter()
+            .map(|(i, (score, candidate))| FuzzyMatch {
+                entity: &self.index.entities()[i],
+                matched_name: candidate.name.to_string(),
+                score,
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(a.entity.cik.cmp(&b.entity.cik))
+        });
+        result.truncate(limit);
+
+        result
+    }
+
+    pub fn best_match(&self, name: &str) -> Option<FuzzyMatch<'a>> {
+        self.search(name, 1).into_iter().next()
+    }
+
+    /// Resolves the `name` column of a CSV file, writing the best match of each row with its
+    /// score and confidence. Returns the number of rows resolved.
+    pub fn resolve_csv(&self, input: &Path, output: &Path) -> Result<usize, FuzzyError> {
+        let mut reader = csv::Reader::from_path(input).context(CsvSnafu { filepath: input })?;
+        let mut writer = csv::Writer::from_path(output).context(CsvSnafu { filepath: output })?;
+
+        let mut count = 0;
+        for row in reader.deserialize() {
+            let row: NameRow = row.context(CsvSnafu { filepath: input })?;
+            let best = self.best_match(&row.name);
+            let resolved = ResolvedName {
+                cik: best.as_ref().map(|m| m.entity.cik),
+                entity_name: best.as_ref().map(|m| m.entity.name().to_string()),
+                score: best.as_ref().map(|m| m.score).unwrap_or_default(),
+                confidence: best.map(|m| m.confidence()).unwrap_or(Confidence::None),
+                name: row.name,
+            };
+
+            writer
+                .serialize(resolved)
+                .context(CsvSnafu { filepath: output })?;
+            count += 1;
+        }
+        writer.flush()?;
+
+        Ok(count)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NameRow {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ResolvedName {
+    name: String,
+    cik: Option<usize>,
+    entity_name: Option<String>,
+    score: f64,
+    confidence: Confidence,
+}
+
+/// `normalize_name` without share class designations such as `CLASS B` or `COMMON STOCK`
+fn normalize_query(name: &str) -> String {
+    let name = normalize_name(name);
+    let mut result: Vec<&str> = vec![];
+    let mut tokens = name.split_whitespace().peekable();
+
+    while let Some(token) = tokens.next() {
+        if !SECURITY_WORDS.contains(&token) {
+            result.push(token);
+            continue;
+        }
+        if matches!(token, "CLASS" | "CL" | "SERIES") {
+            tokens.next_if(|t| t.len() == 1);
+        }
+    }
+
+    match result.is_empty() {
+        true => name,
+        false => result.join(" "),
+    }
+}
+
+fn words(name: &str) -> HashSet<String> {
+    name.split_whitespace().map(str::to_string).collect()
+}
+
+fn token_prefix(token: &str) -> String {
+    token.chars().take(3).collect()
+}
+
+fn trigrams(name: &str) -> HashSet<String> {
+    let padded = format!("  {name} ").chars().collect::<Vec<_>>();
+
+    padded
+        .windows(3)
+        .map(|w| w.iter().collect::<String>())
+        .collect()
+}
+
+/// Average over the words of both names of the similarity to the closest word in the other
+fn token_similarity(a: &[&Token], b: &[&Token]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let closest = |token: &Token, others: &[&Token]| match others
+        .iter()
+        .any(|other| other.text == token.text)
+    {
+        true => 1.0,
+        false => others
+            .iter()
+            .map(|other| dice(&token.trigrams, &other.trigrams))
+            .fold(0.0, f64::max),
+    };
+    let total =
+        a.iter().map(|t| closest(t, b)).sum::<f64>() + b.iter().map(|t| closest(t, a)).sum::<f64>();
+
+    total / (a.len() + b.len()) as f64
+}
+
+fn dice(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cik_lookup::record::test_entity;
+
+    use super::*;
+
+    fn index() -> CikIndex {
+        [
+            test_entity(1652044, &["Alphabet Inc."], None, &[("GOOGL", "")]),
+            test_entity(1067983, &["BERKSHIRE HATHAWAY INC"], None, &[("BRK-B", "")]),
+            test_entity(1, &["BERKSHIRE HILLS BANCORP INC"], None, &[("BHLB", "")]),
+            test_entity(2, &["ALPHA PRO TECH LTD"], None, &[("APT", "")]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn it_ranks_fuzzy_matches() {
+        let index = index();
+        let matcher = FuzzyMatcher::new(&index);
+
+        let alphabet = matcher.best_match("Alphabett, Inc").unwrap();
+        assert_eq!(alphabet.entity.cik, 1652044);
+        assert_eq!(alphabet.confidence(), Confidence::Medium);
+
+        let berkshire = matcher.search("berkshire hathaway class b", FuzzyMatcher::DEFAULT_LIMIT);
+        assert_eq!(berkshire.len(), 2);
+        assert_eq!(berkshire[0].entity.cik, 1067983);
+        assert_eq!(berkshire[0].score, 1.0);
+        assert_eq!(berkshire[1].entity.cik, 1);
+        assert!(berkshire[1].score < 0.75);
+
+        assert!(matcher.best_match("Microsoft").is_none());
+    }
+
+    #[test]
+    fn it_skips_common_word_blocks() {
+        let index = [
+            test_entity(1, &["FIRST ACME CORP"], None, &[("FAC", "")]),
+            test_entity(2, &["FIRST BANCORP"], None, &[("FBP", "")]),
+            test_entity(3, &["FIRST SOLAR INC"], None, &[("FSLR", "")]),
+        ]
+        .into_iter()
+        .collect::<CikIndex>();
+        let mut matcher = FuzzyMatcher::new(&index);
+        assert_eq!(matcher.search("First Acme", 5).len(), 3);
+
+        matcher.max_block_size = 2;
+        let matches = matcher.search("First Acme", 5);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entity.cik, 1);
+        assert_eq!(matcher.search("First", 5).len(), 3);
+    }
+
+    #[test]
+    fn it_resolves_csv() -> Result<(), FuzzyError> {
+        let index = index();
+        let matcher = FuzzyMatcher::new(&index);
+        let input = std::env::temp_dir().join("secparser_fuzzy_input.csv");
+        let output = std::env::temp_dir().join("secparser_fuzzy_output.csv");
+        std::fs::write(&input, "name\nAlphabet Inc.\nUnknown Widgets\n")?;
+
+        assert_eq!(matcher.resolve_csv(&input, &output)?, 2);
+        assert_eq!(
+            std::fs::read_to_string(&output)?,
+            "name,cik,entity_name,score,confidence\n\
+             Alphabet Inc.,1652044,Alphabet Inc.,1.0,High\n\
+             Unknown Widgets,,,0.0,None\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use snafu::{Location, ResultExt, Snafu};
+
+use crate::downloader::DownloadConfig;
+
+use super::record::{CikEntity, CikLookupRecords, CikLookupRecordsError};
+
+/// Legal form suffixes ignored when matching names
+const NAME_SUFFIXES: [&str; 14] = [
+    "INC",
+    "INCORPORATED",
+    "CORP",
+    "CORPORATION",
+    "CO",
+    "COMPANY",
+    "LTD",
+    "LIMITED",
+    "LLC",
+    "LP",
+    "LLP",
+    "PLC",
+    "SA",
+    "NV",
+];
+
+#[derive(Debug, Snafu)]
+pub enum CikIndexError {
+    #[snafu(display("Failed to get CIK lookup records"))]
+    Records { source: CikLookupRecordsError },
+
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to (de)serialize {filepath:?}"))]
+    Json {
+        source: serde_json::Error,
+        filepath: PathBuf,
+    },
+}
+
+/// Normalizes a company name for matching: uppercase, without punctuation, state of
+/// incorporation markers such as `/DE/`, a leading `THE` or legal form suffixes
+pub fn normalize_name(name: &str) -> String {
+    let name = name
+        .split_whitespace()
+        .filter(|token| !token.starts_with('/'))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+        .replace('&', " AND ")
+        .chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c,
+            false => ' ',
+        })
+        .collect::<String>();
+
+    let mut tokens = name.split_whitespace().collect::<Vec<_>>();
+    if tokens.len() > 1 && tokens[0] == "THE" {
+        tokens.remove(0);
+    }
+    while tokens.len() > 1 && tokens.last().is_some_and(|t| NAME_SUFFIXES.contains(t)) {
+        tokens.pop();
+    }
+
+    tokens.join(" ")
+}
+
+/// In-memory lookup of entities by CIK, ticker and name
+#[derive(Debug, Default, Deserialize)]
+#[serde(from = "Vec<CikEntity>")]
+pub struct CikIndex {
+    entities: Vec<CikEntity>,
+    by_cik: HashMap<usize, usize>,
+    by_ticker: HashMap<String, usize>,
+    by_name: BTreeMap<String, Vec<usize>>,
+}
+
+impl CikIndex {
+    pub fn new(download_config: &DownloadConfig) -> Result<Self, CikIndexError> {
+        let records = CikLookupRecords::new(download_config).context(RecordsSnafu)?;
+
+        Ok(records.entities().collect())
+    }
+
+    pub fn load(filepath: &Path) -> Result<Self, CikIndexError> {
+        let reader = BufReader::new(File::open(filepath)?);
+
+        serde_json::from_reader(reader).context(JsonSnafu { filepath })
+    }
+
+    pub fn save(&self, filepath: &Path) -> Result<(), CikIndexError> {
+        let writer = BufWriter::new(File::create(filepath)?);
+
+        serde_json::to_writer(writer, &self.entities).context(JsonSnafu { filepath })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn entities(&self) -> &[CikEntity] {
+        &self.entities
+    }
+
+    /// Adds an entity, replacing the one with the same CIK along with its tickers and names
+    pub fn insert(&mut self, entity: CikEntity) {
+        if let Some(i) = self.by_cik.get(&entity.cik).copied() {
+            let replaced = std::mem::replace(&mut self.entities[i], entity);
+            self.unindex(i, &replaced);
+
+            let entities = std::mem::take(&mut self.entities);
+            self.index(i, &entities[i]);
+            self.entities = entities;
+            return;
+        }
+
+        let i = self.entities.len();
+        self.index(i, &entity);
+        self.entities.push(entity);
+    }
+
+    fn index(&mut self, i: usize, entity: &CikEntity) {
+        self.by_cik.insert(entity.cik, i);
+        for listing in &entity.listings {
+            self.by_ticker.insert(listing.ticker.to_uppercase(), i);
+        }
+
+        let names = entity.names.iter().chain(&entity.current_name);
+        for name in names {
+            let indices = self.by_name.entry(normalize_name(name)).or_default();
+            if !indices.contains(&i) {
+                indices.push(i);
+            }
+        }
+    }
+
+    /// Removes the ticker and name keys of `entity` still pointing to `i`
+    fn unindex(&mut self, i: usize, entity: &CikEntity) {
+        for listing in &entity.listings {
+            let ticker = listing.ticker.to_uppercase();
+            if self.by_ticker.get(&ticker) == Some(&i) {
+                self.by_ticker.remove(&ticker);
+            }
+        }
+
+        let names = entity.names.iter().chain(&entity.current_name);
+        for name in names {
+            let name = normalize_name(name);
+            if let Some(indices) = self.by_name.get_mut(&name) {
+                indices.retain(|j| *j != i);
+                if indices.is_empty() {
+                    self.by_name.remove(&name);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, cik: usize) -> Option<&CikEntity> {
+        self.by_cik.get(&cik).map(|i| &self.entities[*i])
+    }
+
+    /// Exact ticker lookup, ignoring case
+    pub fn ticker(&self, ticker: &str) -> Option<&CikEntity> {
+        self.by_ticker
+            .get(&ticker.trim().to_uppercase())
+            .map(|i| &self.entities[*i])
+    }
+
+    /// Entities with a current or former name matching after `normalize_name`
+    pub fn name(&self, name: &str) -> Vec<&CikEntity> {
+        self.by_name
+            .get(&normalize_name(name))
+            .into_iter()
+            .flatten()
+            .map(|i| &self.entities[*i])
+            .collect()
+    }
+
+    /// Entities with a normalized name starting with the normalized prefix, ordered by name
+    pub fn search_prefix(&self, prefix: &str) -> Vec<&CikEntity> {
+        let prefix = normalize_name(prefix);
+        let mut result: Vec<&CikEntity> = vec![];
+
+        let matches = self
+            .by_name
+            .range(prefix.to_string()..)
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .flat_map(|(_, indices)| indices);
+        for i in matches {
+            let entity = &self.entities[*i];
+            if !result.iter().any(|e| e.cik == entity.cik) {
+                result.push(entity);
+            }
+        }
+
+        result
+    }
+}
+
+impl FromIterator<CikEntity> for CikIndex {
+    fn from_iter<I: IntoIterator<Item = CikEntity>>(iter: I) -> Self {
+        let mut index = Self::default();
+        for entity in iter {
+            index.insert(entity);
+        }
+        index
+    }
+}
+
+impl From<Vec<CikEntity>> for CikIndex {
+    fn from(entities: Vec<CikEntity>) -> Self {
+        entities.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cik_lookup::record::test_entity;
+
+    use super::*;
+
+    fn index() -> CikIndex {
+        [
+            test_entity(
+                320193,
+                &["APPLE COMPUTER INC", "APPLE INC"],
+                Some("Apple Inc."),
+                &[("AAPL", "")],
+            ),
+            test_entity(1, &["APPLE HOSPITALITY REIT, INC."], None, &[("APLE", "")]),
+            test_entity(
+                1067983,
+                &["BERKSHIRE HATHAWAY INC"],
+                None,
+                &[("BRK-B", ""), ("BRK-A", "")],
+            ),
+            test_entity(
+                789019,
+                &["MICROSOFT CORP"],
+                Some("MICROSOFT CORP"),
+                &[("MSFT", "")],
+            ),
+            test_entity(2, &["THE COCA-COLA CO /DE/"], None, &[]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn it_normalizes_names() {
+        assert_eq!(normalize_name("Apple Inc."), "APPLE");
+        assert_eq!(normalize_name("THE COCA-COLA CO /DE/"), "COCA COLA");
+        assert_eq!(normalize_name("Johnson & Johnson"), "JOHNSON AND JOHNSON");
+        assert_eq!(normalize_name("Inc"), "INC");
+    }
+
+    #[test]
+    fn it_resolves_entities() {
+        let index = index();
+
+        assert_eq!(index.ticker("aapl").unwrap().cik, 320193);
+        assert_eq!(index.get(789019).unwrap().ticker(), "MSFT");
+        assert_eq!(index.ticker("brk-a").unwrap().cik, 1067983);
+        assert_eq!(index.ticker("BRK-B").unwrap().cik, 1067983);
+        assert_eq!(index.name("apple computer, inc").len(), 1);
+        assert_eq!(index.name("Coca Cola Company")[0].cik, 2);
+
+        let apple = index
+            .search_prefix("apple")
+            .iter()
+            .map(|e| e.cik)
+            .collect::<Vec<_>>();
+        assert_eq!(apple, vec![320193, 1]);
+    }
+
+    #[test]
+    fn it_replaces_entities() {
+        let mut index = index();
+        index.insert(test_entity(
+            1,
+            &["APPLE HOSPITALITY"],
+            None,
+            &[("APLE2", "")],
+        ));
+        index.insert(test_entity(
+            320193,
+            &["APPLE INC"],
+            Some("Apple Inc."),
+            &[("AAPL", ""), ("APLE", "")],
+        ));
+
+        assert_eq!(index.len(), 5);
+        assert_eq!(index.ticker("APLE2").unwrap().cik, 1);
+        assert_eq!(index.ticker("APLE").unwrap().cik, 320193);
+        assert!(index.name("APPLE HOSPITALITY REIT").is_empty());
+        assert!(index.name("APPLE COMPUTER").is_empty());
+        assert_eq!(index.name("Apple Inc")[0].cik, 320193);
+
+        index.insert(test_entity(320193, &["APPLE INC"], None, &[("AAPL", "")]));
+        assert!(index.ticker("APLE").is_none());
+        assert_eq!(index.ticker("MSFT").unwrap().cik, 789019);
+    }
+
+    #[test]
+    fn it_saves_and_loads() -> Result<(), CikIndexError> {
+        let filepath = std::env::temp_dir().join("secparser_cik_index.json");
+        index().save(&filepath)?;
+
+        let loaded = CikIndex::load(&filepath)?;
+        assert_eq!(loaded.len(), 5);
+        assert_eq!(loaded.ticker("MSFT").unwrap().cik, 789019);
+
+        Ok(())
+    }
+}
@@ -1,2 +1,6 @@
 pub mod data_source;
+pub mod diff;
+pub mod fuzzy;
+pub mod index;
 pub mod record;
+pub mod tickers;
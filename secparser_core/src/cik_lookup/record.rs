@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::cell::OnceCell;
+use std::collections::{btree_map, BTreeMap, HashMap};
 use std::fs::File;
-use std::io::{self, BufReader};
-use std::path::PathBuf;
+use std::io::{self, BufRead, BufReader};
+use std::num::ParseIntError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use snafu::{Location, ResultExt, Snafu};
@@ -11,8 +14,10 @@ use crate::downloader::DownloadConfig;
 use crate::traits::{FileLines, FileReader};
 
 use super::data_source::CikLookupDataSources;
+use super::tickers::{read_company_tickers, read_fund_tickers, FundTicker, Listing};
 
 #[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
 pub enum CikLookupRecordsError {
     #[snafu(display("Failed to get data source"))]
     DataSource { source: DataSourceError },
@@ -30,16 +35,96 @@ pub enum CikLookupRecordsError {
         source: serde_json::Error,
         filepath: PathBuf,
     },
+
+    #[snafu(display("Unexpected fields {fields:?} in {filepath:?}"))]
+    TickersSchema {
+        fields: Vec<String>,
+        filepath: PathBuf,
+    },
+
+    #[snafu(display("Failed to read line {line_number} of cik-lookup-data.txt"))]
+    ReadLine {
+        source: io::Error,
+        line_number: usize,
+    },
+
+    #[snafu(display("Line {line_number} of cik-lookup-data.txt should be NAME:CIK: {line:?}"))]
+    MalformedLine { line_number: usize, line: String },
+
+    #[snafu(display("Invalid CIK on line {line_number} of cik-lookup-data.txt: {cik:?}"))]
+    InvalidCik {
+        source: ParseIntError,
+        line_number: usize,
+        cik: String,
+    },
 }
 
 #[derive(Debug, Serialize)]
 pub struct CikLookup {
     pub cik: usize,
     pub name: String,
-    pub ticker: String,
-    pub exchange: String,
+    /// Tickers of the CIK, in the order of `company_tickers_exchange.json`.
+    /// Shared by every line of the CIK.
+    #[serde(skip)]
+    pub listings: Arc<[Listing]>,
+    /// Mutual fund share classes filed under the CIK
+    #[serde(skip)]
+    pub funds: Arc<[FundTicker]>,
+}
+
+/// All the names a CIK has been known by, with its current listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CikEntity {
+    pub cik: usize,
+    /// Every name the CIK appears under in `cik-lookup-data.txt`, in the alphabetical order
+    /// of the file. The file has no dates, so this is not the order the names were used in.
+    pub names: Vec<String>,
+    /// Name in `company_tickers_exchange.json`, if the company is listed
+    pub current_name: Option<String>,
+    /// Every ticker of the CIK, e.g. one per share class
+    #[serde(default)]
+    pub listings: Vec<Listing>,
+    /// Mutual fund share classes filed under the CIK
+    #[serde(default)]
+    pub funds: Vec<FundTicker>,
+}
+
+impl CikEntity {
+    /// The current name, or the alphabetically first known name for unlisted companies, which
+    /// is not necessarily their latest one
+    pub fn name(&self) -> &str {
+        self.current_name
+            .as_deref()
+            .or(self.names.first().map(String::as_str))
+            .unwrap_or_default()
+    }
+
+    /// The first listed ticker, or an empty string for unlisted companies
+    pub fn ticker(&self) -> &str {
+        self.listings
+            .first()
+            .map(|l| l.ticker.as_str())
+            .unwrap_or_default()
+    }
 }
 
+#[derive(Debug, Default)]
+struct TickerExchange {
+    name: Option<String>,
+    listings: Vec<Listing>,
+}
+
+impl TickerExchange {
+    /// Adds a listing unless the ticker is already known
+    fn add_listing(&mut self, listing: Listing) {
+        if !self.listings.iter().any(|l| l.ticker == listing.ticker) {
+            self.listings.push(listing);
+        }
+    }
+}
+
+const TICKERS_EXCHANGE_FIELDS: [&str; 4] = ["cik", "name", "ticker", "exchange"];
+
 type TickersExchangeFields = Vec<String>;
 type TickersExchangeDataItem = (usize, Option<String>, Option<String>, Option<String>);
 
@@ -50,9 +135,14 @@ struct TickersExchangeData {
 }
 
 pub struct CikLookupRecords {
-    pub count: usize,
     lines: FileLines,
-    tickers_exchange: HashMap<usize, (Option<String>, Option<String>)>,
+    line_number: usize,
+    yielded: usize,
+    lookup_filepath: PathBuf,
+    line_count: OnceCell<usize>,
+    current_names: HashMap<usize, String>,
+    listings: HashMap<usize, Arc<[Listing]>>,
+    funds: HashMap<usize, Arc<[FundTicker]>>,
 }
 
 impl FileReader for CikLookupRecords {}
@@ -60,8 +150,14 @@ impl FileReader for CikLookupRecords {}
 impl CikLookupRecords {
     pub fn new(download_config: &DownloadConfig) -> Result<Self, CikLookupRecordsError> {
         let data_source = CikLookupDataSources::new(download_config).context(DataSourceSnafu)?;
+
+        Self::from_data_sources(&data_source)
+    }
+
+    pub fn from_data_sources(
+        data_source: &CikLookupDataSources,
+    ) -> Result<Self, CikLookupRecordsError> {
         let lines = Self::get_lines(&data_source.lookup_ds.filepath)?;
-        let count = Self::get_lines(&data_source.lookup_ds.filepath)?.count();
 
         let file = File::open(&data_source.tickers_exchange_ds.filepath)?;
         let reader = BufReader::new(file);
@@ -70,74 +166,423 @@ impl CikLookupRecords {
                 filepath: &data_source.tickers_exchange_ds.filepath,
             })?;
 
-        assert_eq!(
-            tickers_exchange.fields,
-            vec!("cik", "name", "ticker", "exchange")
-        );
+        if tickers_exchange.fields != TICKERS_EXCHANGE_FIELDS {
+            return TickersSchemaSnafu {
+                fields: tickers_exchange.fields,
+                filepath: &data_source.tickers_exchange_ds.filepath,
+            }
+            .fail();
+        }
 
-        let tickers_exchange = tickers_exchange
-            .data
-            .into_iter()
-            .map(|item| (item.0, (item.2, item.3)))
-            .collect::<HashMap<_, _>>();
+        let mut by_cik: HashMap<usize, TickerExchange> = HashMap::new();
+        for (cik, name, ticker, exchange) in tickers_exchange.data {
+            let entry = by_cik.entry(cik).or_default();
+            if entry.name.is_none() {
+                entry.name = name;
+            }
+            if let Some(ticker) = ticker {
+                entry.add_listing(Listing {
+                    cik,
+                    ticker,
+                    exchange: exchange.unwrap_or_default(),
+                });
+            }
+        }
+
+        // company_tickers.json also lists companies without an exchange
+        for ticker in read_company_tickers(&data_source.tickers_ds.filepath)? {
+            let entry = by_cik.entry(ticker.cik).or_default();
+            if entry.name.is_none() {
+                entry.name = Some(ticker.title);
+            }
+            entry.add_listing(Listing {
+                cik: ticker.cik,
+                ticker: ticker.ticker,
+                exchange: "".to_string(),
+            });
+        }
+
+        let mut funds: HashMap<usize, Vec<FundTicker>> = HashMap::new();
+        for fund in read_fund_tickers(&data_source.tickers_mf_ds.filepath)? {
+            funds.entry(fund.cik).or_default().push(fund);
+        }
 
         Ok(Self {
             lines,
-            count,
-            tickers_exchange,
+            line_number: 0,
+            yielded: 0,
+            lookup_filepath: data_source.lookup_ds.filepath.clone(),
+            line_count: OnceCell::new(),
+            current_names: by_cik
+                .iter_mut()
+                .filter_map(|(cik, t)| Some((*cik, t.name.take()?)))
+                .collect(),
+            listings: by_cik
+                .into_iter()
+                .map(|(cik, t)| (cik, t.listings.into()))
+                .collect(),
+            funds: funds
+                .into_iter()
+                .map(|(cik, funds)| (cik, funds.into()))
+                .collect(),
         })
     }
 
-    fn parse_one_record(&self, name: &str, cik: &str) -> CikLookup {
+    /// Number of non-blank lines in the lookup file, i.e. the total number of items.
+    ///
+    /// Counted on first use by scanning raw bytes, without decoding the file, and cached.
+    pub fn line_count(&self) -> Result<usize, CikLookupRecordsError> {
+        if let Some(count) = self.line_count.get() {
+            return Ok(*count);
+        }
+
+        let count = count_lines(&self.lookup_filepath)?;
+        let _ = self.line_count.set(count);
+
+        Ok(count)
+    }
+
+    fn parse_one_record(&self, line: &str) -> Result<CikLookup, CikLookupRecordsError> {
+        let line_number = self.line_number;
+        let trimmed = line.trim_end();
+        let trimmed = trimmed.strip_suffix(':').unwrap_or(trimmed);
+        let Some((name, cik)) = trimmed.rsplit_once(':') else {
+            return MalformedLineSnafu { line_number, line }.fail();
+        };
+
         let cik = cik
+            .trim()
             .parse::<usize>()
-            .unwrap_or_else(|e| panic!("Should parse cik: {e}"));
-        let (ticker, exchange) = self.tickers_exchange.get(&cik).unwrap_or(&(None, None));
+            .context(InvalidCikSnafu { line_number, cik })?;
         let name = name.to_string();
-        let ticker = match ticker {
-            Some(v) => v.to_string(),
-            None => "".to_string(),
-        };
-        let exchange = match exchange {
-            Some(v) => v.to_string(),
-            None => "".to_string(),
-        };
+        let listings = self.listings.get(&cik).cloned().unwrap_or_default();
+        let funds = self.funds.get(&cik).cloned().unwrap_or_default();
 
-        CikLookup {
+        Ok(CikLookup {
             cik,
             name,
-            ticker,
-            exchange,
+            listings,
+            funds,
+        })
+    }
+}
+
+impl CikLookupRecords {
+    /// Every listing of every CIK, ordered by CIK, without reading the lookup file
+    pub fn into_listings(self) -> Vec<Listing> {
+        let mut listings = self
+            .listings
+            .into_values()
+            .flat_map(|listings| listings.to_vec())
+            .collect::<Vec<_>>();
+        listings.sort_by_key(|l| l.cik);
+
+        listings
+    }
+
+    /// Aggregates the remaining records into one `CikEntity` per CIK, ordered by CIK.
+    /// Lines that fail to parse are skipped and kept in `CikEntities::errors`.
+    pub fn entities(mut self) -> CikEntities {
+        let mut entities: BTreeMap<usize, CikEntity> = BTreeMap::new();
+        let mut errors = vec![];
+
+        for record in self.by_ref() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+
+            let entity = entities.entry(record.cik).or_insert_with(|| CikEntity {
+                cik: record.cik,
+                names: vec![],
+                current_name: None,
+                listings: record.listings.to_vec(),
+                funds: record.funds.to_vec(),
+            });
+            if !entity.names.contains(&record.name) {
+                entity.names.push(record.name);
+            }
+        }
+
+        for (cik, entity) in entities.iter_mut() {
+            entity.current_name = self.current_names.remove(cik);
         }
+
+        CikEntities {
+            entities: entities.into_values(),
+            errors,
+        }
+    }
+}
+
+pub struct CikEntities {
+    entities: btree_map::IntoValues<usize, CikEntity>,
+    /// Lines skipped while aggregating
+    pub errors: Vec<CikLookupRecordsError>,
+}
+
+impl Iterator for CikEntities {
+    type Item = CikEntity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entities.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entities.size_hint()
     }
 }
 
+impl ExactSizeIterator for CikEntities {}
+
 impl Iterator for CikLookupRecords {
-    type Item = CikLookup;
+    type Item = Result<CikLookup, CikLookupRecordsError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.lines.next() {
-            Some(line) => {
-                let line =
-                    line.unwrap_or_else(|e| panic!("Should get line in cik-lookup-data.txt: {e}"));
-                let line = &line[..line.len() - 1];
-
-                line.rsplit_once(":")
-                    .map(|(name, cik)| self.parse_one_record(name, cik))
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+
+            let line = match line.context(ReadLineSnafu {
+                line_number: self.line_number,
+            }) {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if is_blank(line.as_bytes()) {
+                continue;
             }
-            None => None,
+
+            self.yielded += 1;
+
+            return Some(self.parse_one_record(&line));
         }
     }
+
+    /// Exact once the line count is known. The count is computed on the first call, and if
+    /// that fails the size is unknown.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let line_count = match self.line_count() {
+            Ok(count) => count,
+            Err(e) => {
+                log::warn!("Failed to count lines in cik-lookup-data.txt: {e}");
+                return (0, None);
+            }
+        };
+
+        let remaining = line_count.saturating_sub(self.yielded);
+        (remaining, Some(remaining))
+    }
+}
+
+/// Lines with only ASCII whitespace are skipped, both when parsing and when counting
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Counts non-blank lines by scanning raw bytes, matching the lines parsed as records
+fn count_lines(filepath: &Path) -> Result<usize, io::Error> {
+    let mut reader = BufReader::new(File::open(filepath)?);
+    let mut count = 0;
+    let mut line_is_blank = true;
+
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+
+        for byte in buf {
+            match byte {
+                b'\n' => {
+                    if !line_is_blank {
+                        count += 1;
+                    }
+                    line_is_blank = true;
+                }
+                b if b.is_ascii_whitespace() => {}
+                _ => line_is_blank = false,
+            }
+        }
+
+        let len = buf.len();
+        reader.consume(len);
+    }
+    if !line_is_blank {
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// An entity with the given names and `(ticker, exchange)` listings
+#[cfg(test)]
+pub(crate) fn test_entity(
+    cik: usize,
+    names: &[&str],
+    current_name: Option<&str>,
+    listings: &[(&str, &str)],
+) -> CikEntity {
+    CikEntity {
+        cik,
+        names: names.iter().map(|n| n.to_string()).collect(),
+        current_name: current_name.map(str::to_string),
+        listings: listings
+            .iter()
+            .map(|(ticker, exchange)| Listing {
+                cik,
+                ticker: ticker.to_string(),
+                exchange: exchange.to_string(),
+            })
+            .collect(),
+        funds: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use snafu::{ResultExt, Whatever};
 
+    use crate::data_source::DataSource;
     use crate::downloader::DownloadConfigBuilder;
 
     use super::*;
 
+    fn test_data_sources(name: &str, lookup_content: &str) -> CikLookupDataSources {
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&dir).unwrap_or_else(|e| panic!("Should create dir: {e}"));
+
+        let lookup = dir.join("cik-lookup-data.txt");
+        std::fs::write(&lookup, lookup_content)
+            .unwrap_or_else(|e| panic!("Should write lookup: {e}"));
+
+        let tickers_exchange = dir.join("company_tickers_exchange.json");
+        std::fs::write(
+            &tickers_exchange,
+            r#"{"fields":["cik","name","ticker","exchange"],"data":[[1652044,"Alphabet Inc.","GOOGL","Nasdaq"],[320193,"Apple Inc.","AAPL","Nasdaq"],[1652044,"Alphabet Inc.","GOOG","Nasdaq"]]}"#,
+        )
+        .unwrap_or_else(|e| panic!("Should write tickers: {e}"));
+
+        let tickers = dir.join("company_tickers.json");
+        std::fs::write(
+            &tickers,
+            r#"{"0":{"cik_str":320193,"ticker":"AAPL","title":"Apple Inc."},"1":{"cik_str":42,"ticker":"JDOE","title":"John Doe"}}"#,
+        )
+        .unwrap_or_else(|e| panic!("Should write tickers: {e}"));
+
+        let tickers_mf = dir.join("company_tickers_mf.json");
+        std::fs::write(
+            &tickers_mf,
+            r#"{"fields":["cik","seriesId","classId","symbol"],"data":[[42,"S000000001","C000000001","JDOEX"],[42,"S000000001","C000000002","JDOIX"]]}"#,
+        )
+        .unwrap_or_else(|e| panic!("Should write fund tickers: {e}"));
+
+        CikLookupDataSources {
+            lookup_ds: DataSource { filepath: lookup },
+            tickers_exchange_ds: DataSource {
+                filepath: tickers_exchange,
+            },
+            tickers_ds: DataSource { filepath: tickers },
+            tickers_mf_ds: DataSource {
+                filepath: tickers_mf,
+            },
+        }
+    }
+
+    #[test]
+    fn it_aggregates_names_per_cik() -> Result<(), CikLookupRecordsError> {
+        let data_sources = test_data_sources(
+            "secparser_cik_entities",
+            "APPLE COMPUTER INC:0000320193:\nAPPLE INC:0000320193:\n \nJOHN DOE:0000000042:\n",
+        );
+        let mut records = CikLookupRecords::from_data_sources(&data_sources)?;
+        assert_eq!(records.size_hint(), (3, Some(3)));
+        assert!(records.next().is_some());
+        assert_eq!(records.size_hint(), (2, Some(2)));
+        assert_eq!(records.line_count()?, 3);
+
+        let entities = CikLookupRecords::from_data_sources(&data_sources)?.entities();
+        assert_eq!(entities.len(), 2);
+        let entities = entities.collect::<Vec<_>>();
+
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].name(), "John Doe");
+        assert_eq!(entities[0].ticker(), "JDOE");
+        assert_eq!(entities[0].listings[0].exchange, "");
+        assert_eq!(entities[0].funds.len(), 2);
+        assert_eq!(entities[0].funds[1].symbol, "JDOIX");
+        assert_eq!(entities[1].names, vec!["APPLE COMPUTER INC", "APPLE INC"]);
+        assert_eq!(entities[1].name(), "Apple Inc.");
+        assert_eq!(entities[1].listings.len(), 1);
+        assert_eq!(entities[1].listings[0].exchange, "Nasdaq");
+        assert!(entities[1].funds.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_every_listing() -> Result<(), CikLookupRecordsError> {
+        let data_sources =
+            test_data_sources("secparser_cik_listings", "ALPHABET INC.:0001652044:\n");
+
+        let alphabet = CikLookupRecords::from_data_sources(&data_sources)?
+            .next()
+            .unwrap()?;
+        let tickers = alphabet
+            .listings
+            .iter()
+            .map(|l| l.ticker.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(tickers, vec!["GOOGL", "GOOG"]);
+
+        let listings = CikLookupRecords::from_data_sources(&data_sources)?.into_listings();
+        assert_eq!(listings.len(), 4);
+        assert_eq!(listings[0].ticker, "JDOE");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_reports_malformed_lines() -> Result<(), CikLookupRecordsError> {
+        let data_sources = test_data_sources(
+            "secparser_cik_malformed",
+            "APPLE INC:0000320193\n\nNO CIK\nBAD CIK:12AB:\nJOHN DOE:0000000042:\n",
+        );
+        let records = CikLookupRecords::from_data_sources(&data_sources)?;
+        assert_eq!(records.line_count()?, 4);
+        let records = records.collect::<Vec<_>>();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].as_ref().unwrap().cik, 320193);
+        assert!(matches!(
+            records[1],
+            Err(CikLookupRecordsError::MalformedLine { line_number: 3, .. })
+        ));
+        assert!(matches!(
+            records[2],
+            Err(CikLookupRecordsError::InvalidCik { line_number: 4, .. })
+        ));
+        assert_eq!(records[3].as_ref().unwrap().name, "JOHN DOE");
+
+        let entities = CikLookupRecords::from_data_sources(&data_sources)?.entities();
+        assert_eq!(entities.errors.len(), 2);
+        assert_eq!(entities.count(), 2);
+
+        std::fs::write(
+            &data_sources.tickers_exchange_ds.filepath,
+            r#"{"fields":["cik","ticker"],"data":[]}"#,
+        )?;
+        assert!(matches!(
+            CikLookupRecords::from_data_sources(&data_sources),
+            Err(CikLookupRecordsError::TickersSchema { .. })
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn it_parses_cik_lookup() -> Result<(), Whatever> {
         env_logger::builder()
@@ -155,6 +600,7 @@ mod tests {
             CikLookupRecords::new(&download_config).whatever_context("Failed to create records")?;
 
         for r in records {
+            let r = r.whatever_context("Failed to parse record")?;
             log::debug!("{r:?}");
         }
 
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::data_source::DataSource;
+use crate::downloader::DownloadConfig;
+
+use super::data_source::CikLookupDataSources;
+use super::record::{CikLookupRecordsError, DataSourceSnafu, DeserializeSnafu, TickersSchemaSnafu};
+
+const TICKERS_MF_FIELDS: [&str; 4] = ["cik", "seriesId", "classId", "symbol"];
+
+/// Row of `company_tickers.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyTicker {
+    #[serde(alias = "cik_str")]
+    pub cik: usize,
+    pub ticker: String,
+    pub title: String,
+}
+
+/// A ticker a CIK trades under. Companies with several share classes have one per class.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Listing {
+    pub cik: usize,
+    pub ticker: String,
+    /// Empty when the ticker is only known from `company_tickers.json`
+    pub exchange: String,
+}
+
+/// Row of `company_tickers_mf.json`: a share class of a mutual fund series
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundTicker {
+    pub cik: usize,
+    pub series_id: String,
+    pub class_id: String,
+    pub symbol: String,
+}
+
+type TickersMfDataItem = (usize, Option<String>, Option<String>, Option<String>);
+
+#[derive(Debug, Deserialize)]
+struct TickersMfData {
+    fields: Vec<String>,
+    data: Vec<TickersMfDataItem>,
+}
+
+/// Reads `company_tickers.json`, ordered as in the file (by market capitalization)
+pub fn read_company_tickers(filepath: &Path) -> Result<Vec<CompanyTicker>, CikLookupRecordsError> {
+    let reader = BufReader::new(File::open(filepath)?);
+    let tickers: BTreeMap<usize, CompanyTicker> =
+        serde_json::from_reader(reader).context(DeserializeSnafu { filepath })?;
+
+    Ok(tickers.into_values().collect())
+}
+
+/// Reads `company_tickers_mf.json`, skipping rows without a class ID
+pub fn read_fund_tickers(filepath: &Path) -> Result<Vec<FundTicker>, CikLookupRecordsError> {
+    let reader = BufReader::new(File::open(filepath)?);
+    let tickers: TickersMfData =
+        serde_json::from_reader(reader).context(DeserializeSnafu { filepath })?;
+
+    if tickers.fields != TICKERS_MF_FIELDS {
+        return TickersSchemaSnafu {
+            fields: tickers.fields,
+            filepath,
+        }
+        .fail();
+    }
+
+    let funds = tickers
+        .data
+        .into_iter()
+        .filter_map(|(cik, series_id, class_id, symbol)| {
+            let Some(class_id) = class_id.filter(|c| !c.is_empty()) else {
+                log::warn!("Skipping fund ticker {symbol:?} of CIK {cik} without a class ID");
+                return None;
+            };
+
+            Some(FundTicker {
+                cik,
+                series_id: series_id.unwrap_or_default(),
+                class_id,
+                symbol: symbol.unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok(funds)
+}
+
+/// Downloads and reads `company_tickers_mf.json` on its own
+pub fn fund_tickers(
+    download_config: &DownloadConfig,
+) -> Result<Vec<FundTicker>, CikLookupRecordsError> {
+    let data_source = DataSource::new(download_config, CikLookupDataSources::TICKERS_MF_URL)
+        .context(DataSourceSnafu)?;
+
+    read_fund_tickers(&data_source.filepath)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reads_ticker_files() -> Result<(), CikLookupRecordsError> {
+        let dir = std::env::temp_dir();
+        let tickers = dir.join("secparser_company_tickers.json");
+        std::fs::write(
+            &tickers,
+            r#"{"0":{"cik_str":320193,"ticker":"AAPL","title":"Apple Inc."},"1":{"cik_str":789019,"ticker":"MSFT","title":"MICROSOFT CORP"}}"#,
+        )?;
+        let tickers = read_company_tickers(&tickers)?;
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[0].cik, 320193);
+
+        let funds = dir.join("secparser_company_tickers_mf.json");
+        std::fs::write(
+            &funds,
+            r#"{"fields":["cik","seriesId","classId","symbol"],"data":[[2110,"S000009184","C000024954","LACAX"],[2110,"S000009184","C000024956",null],[2110,"S000009184",null,"LAXXX"]]}"#,
+        )?;
+        let funds = read_fund_tickers(&funds)?;
+        assert_eq!(funds.len(), 2);
+        assert_eq!(funds[0].series_id, "S000009184");
+        assert_eq!(funds[1].symbol, "");
+
+        Ok(())
+    }
+}
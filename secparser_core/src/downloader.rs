@@ -125,7 +125,7 @@ impl Downloader {
         let parsed_url = Url::parse(url).context(ParseUrlSnafu { url })?;
         let filename = parsed_url
             .path_segments()
-            .and_then(|segments| segments.last())
+            .and_then(|mut segments| segments.next_back())
             .unwrap_or_else(|| panic!("Should parse filename from {url}"));
 
         Ok(Path::new(&self.config.download_dir).join(filename))
@@ -138,13 +138,18 @@ impl Downloader {
             .connect_timeout(Duration::from_secs(3))
             .build()
             .context(ClientBuilderSnafu)?;
+        // Files are served from both www.sec.gov and data.sec.gov
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| "www.sec.gov".to_string());
         // See https://www.sec.gov/search-filings/edgar-search-assistance/accessing-edgar-data
         // Section "Fair Access"
         let response = client
             .get(url)
             .header(USER_AGENT, self.config.user_agent.to_string())
             .header(ACCEPT_ENCODING, "gzip,deflate")
-            .header(HOST, "www.sec.gov")
+            .header(HOST, host)
             .send()
             .context(DownloadSnafu { url })?;
         let mut dest = File::create(filepath)?;
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use super::codes::FiscalPeriod;
+use super::num_record::FsNum;
+use super::sub_record::FsSub;
+
+/// Reporting period of a company, shared by a filing and its amendments
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeriodKey {
+    pub cik: usize,
+    pub fy: Option<i32>,
+    pub fp: Option<FiscalPeriod>,
+}
+
+impl From<&FsSub> for PeriodKey {
+    fn from(sub: &FsSub) -> Self {
+        Self {
+            cik: sub.cik,
+            fy: sub.fy,
+            fp: sub.fp.clone(),
+        }
+    }
+}
+
+/// Which of several values reported for the same fact to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumView {
+    /// The value from the earliest accepted filing
+    FirstReported,
+    /// The value from the most recently accepted filing, including amendments and
+    /// comparative periods of later filings
+    LatestRestated,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FactKey {
+    cik: usize,
+    tag: String,
+    ddate: NaiveDate,
+    qtrs: Option<u16>,
+    uom: String,
+    dimh: String,
+    coreg: String,
+}
+
+/// Groups submissions by reporting period to resolve amended and restated filings
+#[derive(Debug, Default)]
+pub struct AmendmentResolver {
+    periods: BTreeMap<PeriodKey, Vec<FsSub>>,
+    filings: HashMap<String, (usize, NaiveDateTime)>,
+}
+
+impl AmendmentResolver {
+    pub fn insert(&mut self, sub: FsSub) {
+        if self.filings.contains_key(&sub.adsh) {
+            return;
+        }
+
+        self.filings
+            .insert(sub.adsh.to_string(), (sub.cik, sub.accepted));
+        let subs = self.periods.entry(PeriodKey::from(&sub)).or_default();
+        let i = subs.partition_point(|s| s.accepted <= sub.accepted);
+        subs.insert(i, sub);
+    }
+
+    pub fn periods(&self) -> impl Iterator<Item = &PeriodKey> {
+        self.periods.keys()
+    }
+
+    /// All filings for the period, ordered by acceptance
+    pub fn filings(&self, key: &PeriodKey) -> &[FsSub] {
+        self.periods.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The original filing for the period, ignoring amendments unless there is nothing else
+    pub fn original(&self, key: &PeriodKey) -> Option<&FsSub> {
+        let subs = self.filings(key);
+
+        subs.iter()
+            .find(|s| !s.form.is_amendment())
+            .or_else(|| subs.first())
+    }
+
+    /// The latest authoritative filing for the period: the most recently accepted filing not
+    /// marked as amended by `prevrpt`, or the most recently accepted one if all are
+    pub fn latest(&self, key: &PeriodKey) -> Option<&FsSub> {
+        let subs = self.filings(key);
+
+        subs.iter()
+            .rev()
+            .find(|s| s.prevrpt != Some(1))
+            .or_else(|| subs.last())
+    }
+
+    pub fn latest_filings(&self) -> impl Iterator<Item = &FsSub> {
+        self.periods.keys().filter_map(|key| self.latest(key))
+    }
+
+    pub fn original_filings(&self) -> impl Iterator<Item = &FsSub> {
+        self.periods.keys().filter_map(|key| self.original(key))
+    }
+
+    /// Keeps one value per fact (CIK, tag, date, duration, unit, dimensions and co-registrant).
+    ///
+    /// Values from filings that were not inserted are dropped.
+    pub fn resolve_nums<I: IntoIterator<Item = FsNum>>(
+        &self,
+        nums: I,
+        view: NumView,
+    ) -> Vec<FsNum> {
+        let mut facts: HashMap<FactKey, (NaiveDateTime, FsNum)> = HashMap::new();
+
+        for num in nums {
+            let Some((cik, accepted)) = self.filings.get(&num.adsh) else {
+                continue;
+            };
+
+            let key = FactKey {
+                cik: *cik,
+                tag: num.tag.to_string(),
+                ddate: num.ddate,
+                qtrs: num.qtrs,
+                uom: num.uom.to_string(),
+                dimh: num.dimh.to_string(),
+                coreg: num.coreg.to_string(),
+            };
+            let replace = facts.get(&key).is_none_or(|(current, _)| match view {
+                NumView::FirstReported => accepted < current,
+                NumView::LatestRestated => accepted > current,
+            });
+            if replace {
+                facts.insert(key, (*accepted, num));
+            }
+        }
+
+        let mut result = facts.into_values().collect::<Vec<_>>();
+        result.sort_by(|(a, x), (b, y)| {
+            (a, &x.adsh, &x.tag, x.ddate, x.qtrs).cmp(&(b, &y.adsh, &y.tag, y.ddate, y.qtrs))
+        });
+
+        result.into_iter().map(|(_, num)| num).collect()
+    }
+}
+
+impl FromIterator<FsSub> for AmendmentResolver {
+    fn from_iter<I: IntoIterator<Item = FsSub>>(iter: I) -> Self {
+        let mut resolver = Self::default();
+        for sub in iter {
+            resolver.insert(sub);
+        }
+        resolver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{
+        num_line, parse_tsv, sub_line, SubLine, NUM_HEADER, SUB_HEADER,
+    };
+
+    use super::*;
+
+    fn resolver() -> AmendmentResolver {
+        let original = SubLine {
+            prevrpt: 1,
+            ..SubLine::new("k", 1, "10-K")
+        };
+        let amendment = SubLine {
+            accepted: "2024-05-01 09:00:00.0",
+            ..SubLine::new("ka", 1, "10-K/A")
+        };
+        let next_year = SubLine {
+            period: "20241231",
+            fy: "2024",
+            accepted: "2025-02-15 16:05:00.0",
+            ..SubLine::new("k2", 1, "10-K")
+        };
+
+        parse_tsv::<FsSub>(
+            SUB_HEADER,
+            &[sub_line(amendment), sub_line(original), sub_line(next_year)],
+        )
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn it_resolves_amended_filings() {
+        let resolver = resolver();
+        let key = PeriodKey {
+            cik: 1,
+            fy: Some(2023),
+            fp: Some(FiscalPeriod::FY),
+        };
+
+        assert_eq!(resolver.periods().count(), 2);
+        assert_eq!(resolver.filings(&key).len(), 2);
+        assert_eq!(resolver.original(&key).unwrap().adsh, "k");
+        assert_eq!(resolver.latest(&key).unwrap().adsh, "ka");
+        assert_eq!(
+            resolver
+                .latest_filings()
+                .map(|s| s.adsh.as_str())
+                .collect::<Vec<_>>(),
+            vec!["ka", "k2"]
+        );
+    }
+
+    #[test]
+    fn it_resolves_restated_values() {
+        let resolver = resolver();
+        let nums = parse_tsv::<FsNum>(
+            NUM_HEADER,
+            &[
+                num_line("k2", "Revenues", "20231231", 4, "USD", 103.0),
+                num_line("k", "Revenues", "20231231", 4, "USD", 100.0),
+                num_line("ka", "Revenues", "20231231", 4, "USD", 102.0),
+                num_line("k2", "Revenues", "20241231", 4, "USD", 120.0),
+                num_line("unknown", "Revenues", "20231231", 4, "USD", 1.0),
+            ],
+        );
+
+        let values = |view| {
+            resolver
+                .resolve_nums(nums.clone(), view)
+                .into_iter()
+                .filter_map(|n| n.value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(NumView::FirstReported), vec![100.0, 120.0]);
+        assert_eq!(values(NumView::LatestRestated), vec![103.0, 120.0]);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Serialize;
+
+use super::date;
+use super::filing::FsFiling;
+use super::statement::NO_DIMENSIONS_HASH;
+
+/// A value together with the filing that made it public
+#[derive(Debug, Clone, Serialize)]
+pub struct AsOfValue {
+    pub adsh: String,
+    pub cik: usize,
+    pub tag: String,
+    #[serde(with = "date::yyyymmdd")]
+    pub ddate: NaiveDate,
+    pub qtrs: u16,
+    pub uom: String,
+    pub value: f64,
+    #[serde(with = "date::yyyymmdd")]
+    pub filed: NaiveDate,
+    #[serde(with = "date::datetime")]
+    pub accepted: NaiveDateTime,
+}
+
+/// Consolidated values per CIK, tag and unit, queryable by the time they became public.
+///
+/// Knowledge times are compared against `FsSub::accepted`, so a filing accepted after the
+/// market close is not known earlier that day. Values of a tag in different units, e.g. a
+/// monetary value in USD and in EUR, are kept apart.
+#[derive(Debug, Default)]
+pub struct PointInTime {
+    values: HashMap<(usize, String, String), Vec<AsOfValue>>,
+}
+
+impl PointInTime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the values of an original filing or an amendment
+    pub fn add_filing(&mut self, filing: &FsFiling) {
+        let sub = &filing.sub;
+        let nums = filing
+            .nums
+            .iter()
+            .filter(|n| n.dimh == NO_DIMENSIONS_HASH && n.coreg.is_empty());
+
+        for num in nums {
+            let (Some(value), Some(qtrs)) = (num.value, num.qtrs) else {
+                continue;
+            };
+
+            let values = self
+                .values
+                .entry((sub.cik, num.tag.to_string(), num.uom.to_string()))
+                .or_default();
+            let value = AsOfValue {
+                adsh: sub.adsh.to_string(),
+                cik: sub.cik,
+                tag: num.tag.to_string(),
+                ddate: num.ddate,
+                qtrs,
+                uom: num.uom.to_string(),
+                value,
+                filed: sub.filed,
+                accepted: sub.accepted,
+            };
+            let i = values.partition_point(|v| v.accepted <= sub.accepted);
+            values.insert(i, value);
+        }
+    }
+
+    /// All values of a tag in a unit, ordered by acceptance time
+    pub fn history(&self, cik: usize, tag: &str, uom: &str) -> &[AsOfValue] {
+        self.values
+            .get(&(cik, tag.to_string(), uom.to_string()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Values accepted at or before the knowledge time
+    pub fn known_on(
+        &self,
+        cik: usize,
+        tag: &str,
+        uom: &str,
+        knowledge: NaiveDateTime,
+    ) -> &[AsOfValue] {
+        let history = self.history(cik, tag, uom);
+        let known = history.partition_point(|v| v.accepted <= knowledge);
+
+        &history[..known]
+    }
+
+    /// The value for the most recent period of `qtrs` quarters known at the knowledge time,
+    /// as last reported or restated by then. Use `qtrs = 0` for point-in-time values.
+    pub fn value_as_of(
+        &self,
+        cik: usize,
+        tag: &str,
+        uom: &str,
+        qtrs: u16,
+        knowledge: NaiveDateTime,
+    ) -> Option<&AsOfValue> {
+        self.known_on(cik, tag, uom, knowledge)
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.qtrs == qtrs)
+            .max_by_key(|(i, v)| (v.ddate, *i))
+            .map(|(_, v)| v)
+    }
+
+    /// `value_as_of` at the end of a day, including filings accepted after the market close
+    pub fn value_as_of_date(
+        &self,
+        cik: usize,
+        tag: &str,
+        uom: &str,
+        qtrs: u16,
+        date: NaiveDate,
+    ) -> Option<&AsOfValue> {
+        self.value_as_of(cik, tag, uom, qtrs, end_of_day(date))
+    }
+
+    /// The value for a given period as known at the knowledge time
+    pub fn period_as_of(
+        &self,
+        cik: usize,
+        tag: &str,
+        uom: &str,
+        ddate: NaiveDate,
+        qtrs: u16,
+        knowledge: NaiveDateTime,
+    ) -> Option<&AsOfValue> {
+        self.known_on(cik, tag, uom, knowledge)
+            .iter()
+            .rev()
+            .find(|v| v.ddate == ddate && v.qtrs == qtrs)
+    }
+}
+
+/// The last instant of a day
+fn end_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{fs_filing, num_line, SubLine};
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        date(y, m, d).and_hms_opt(h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn it_queries_values_as_of_a_date() {
+        let mut point_in_time = PointInTime::new();
+        point_in_time.add_filing(&fs_filing(
+            SubLine::new("k", 1, "10-K"),
+            &[num_line("k", "Revenues", "20231231", 4, "USD", 100.0)],
+            &[],
+            &[],
+        ));
+        point_in_time.add_filing(&fs_filing(
+            SubLine {
+                filed: "20240501",
+                accepted: "2024-05-01 09:00:00.0",
+                ..SubLine::new("ka", 1, "10-K/A")
+            },
+            &[num_line("ka", "Revenues", "20231231", 4, "USD", 102.0)],
+            &[],
+            &[],
+        ));
+        point_in_time.add_filing(&fs_filing(
+            SubLine {
+                period: "20241231",
+                filed: "20250214",
+                accepted: "2025-02-14 16:05:00.0",
+                ..SubLine::new("k2", 1, "10-K")
+            },
+            &[
+                num_line("k2", "Revenues", "20241231", 4, "USD", 120.0),
+                num_line("k2", "Revenues", "20231231", 4, "USD", 103.0),
+                num_line("k2", "Revenues", "20241231", 4, "EUR", 110.0),
+            ],
+            &[],
+            &[],
+        ));
+
+        let value_as_of = |d| {
+            point_in_time
+                .value_as_of(1, "Revenues", "USD", 4, d)
+                .map(|v| v.value)
+        };
+        assert_eq!(value_as_of(at(2024, 2, 14, 12, 0)), None);
+        // Accepted at 16:05, after the close of the same day
+        assert_eq!(value_as_of(at(2024, 2, 15, 16, 0)), None);
+        assert_eq!(value_as_of(at(2024, 2, 15, 16, 5)), Some(100.0));
+        assert_eq!(value_as_of(at(2024, 6, 1, 0, 0)), Some(102.0));
+        assert_eq!(value_as_of(at(2025, 2, 14, 16, 0)), Some(102.0));
+        assert_eq!(value_as_of(at(2025, 3, 1, 0, 0)), Some(120.0));
+
+        let value_as_of_date = |d| {
+            point_in_time
+                .value_as_of_date(1, "Revenues", "USD", 4, d)
+                .map(|v| v.value)
+        };
+        assert_eq!(value_as_of_date(date(2025, 2, 13)), Some(102.0));
+        assert_eq!(value_as_of_date(date(2025, 2, 14)), Some(120.0));
+        assert_eq!(
+            point_in_time
+                .value_as_of_date(1, "Revenues", "EUR", 4, date(2025, 2, 14))
+                .map(|v| v.value),
+            Some(110.0)
+        );
+
+        let fy2023_as_of = |d| {
+            point_in_time
+                .period_as_of(1, "Revenues", "USD", date(2023, 12, 31), 4, d)
+                .map(|v| v.adsh.as_str())
+        };
+        assert_eq!(fy2023_as_of(at(2024, 6, 1, 0, 0)), Some("ka"));
+        assert_eq!(fy2023_as_of(at(2025, 3, 1, 0, 0)), Some("k2"));
+        assert_eq!(point_in_time.history(1, "Revenues", "USD").len(), 4);
+        assert_eq!(point_in_time.history(1, "Revenues", "EUR").len(), 1);
+    }
+}
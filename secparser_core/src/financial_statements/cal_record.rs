@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsCal {
     pub adsh: String,
     pub grp: Option<u8>,
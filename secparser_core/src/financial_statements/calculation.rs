@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::date;
+use super::filing::FsFiling;
+use super::num_record::FsNum;
+use super::statement::NO_DIMENSIONS_HASH;
+
+/// `dcml` used by DERA for values reported with infinite precision
+const INFINITE_DECIMALS: f32 = 32767.0;
+
+/// A parent value that differs from the weighted sum of its children by more than rounding
+#[derive(Debug, Clone, Serialize)]
+pub struct CalculationInconsistency {
+    pub adsh: String,
+    pub grp: u8,
+    pub ptag: String,
+    pub pversion: String,
+    #[serde(with = "date::yyyymmdd")]
+    pub ddate: NaiveDate,
+    pub qtrs: u16,
+    pub uom: String,
+    pub reported: f64,
+    pub computed: f64,
+    pub tolerance: f64,
+    /// Children of the calculation without a value for the period, separated by `;`
+    pub missing_children: String,
+}
+
+impl CalculationInconsistency {
+    pub fn difference(&self) -> f64 {
+        self.reported - self.computed
+    }
+}
+
+struct CalculationChild<'a> {
+    tag: &'a str,
+    version: &'a str,
+    weight: f64,
+}
+
+/// Checks the calculation relationships in `FsCal` against the values in `FsNum`.
+///
+/// Only values without dimensions or co-registrants are checked. A parent is compared with the
+/// children reported for the same period and unit; calculations without any such child are
+/// skipped, and children without a value are listed in `missing_children`.
+pub fn validate_calculations(filing: &FsFiling) -> Vec<CalculationInconsistency> {
+    let mut calculations: BTreeMap<(u8, &str, &str), Vec<CalculationChild>> = BTreeMap::new();
+    for cal in &filing.cals {
+        calculations
+            .entry((cal.grp.unwrap_or_default(), &cal.ptag, &cal.pversion))
+            .or_default()
+            .push(CalculationChild {
+                tag: &cal.ctag,
+                version: &cal.cversion,
+                weight: if cal.negative == Some(1) { -1.0 } else { 1.0 },
+            });
+    }
+
+    let nums = filing
+        .nums
+        .iter()
+        .filter(|n| n.dimh == NO_DIMENSIONS_HASH && n.coreg.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::new();
+    for ((grp, ptag, pversion), children) in calculations {
+        let parents = nums
+            .iter()
+            .filter(|n| n.tag == ptag && n.version == pversion);
+
+        for parent in parents {
+            let (Some(reported), Some(qtrs)) = (parent.value, parent.qtrs) else {
+                continue;
+            };
+
+            let mut computed = 0.0;
+            let mut tolerance = rounding_tolerance(parent);
+            let mut found = 0;
+            let mut missing = Vec::new();
+
+            for child in &children {
+                let child_num = nums.iter().find(|n| {
+                    n.tag == child.tag
+                        && n.version == child.version
+                        && n.ddate == parent.ddate
+                        && n.qtrs == parent.qtrs
+                        && n.uom == parent.uom
+                        && n.value.is_some()
+                });
+
+                match child_num {
+                    Some(n) => {
+                        computed += child.weight * n.value.unwrap_or_default();
+                        tolerance += rounding_tolerance(n);
+                        found += 1;
+                    }
+                    None => missing.push(child.tag),
+                }
+            }
+
+            if found == 0 || (reported - computed).abs() <= tolerance {
+                continue;
+            }
+
+            result.push(CalculationInconsistency {
+                adsh: filing.sub.adsh.to_string(),
+                grp,
+                ptag: ptag.to_string(),
+                pversion: pversion.to_string(),
+                ddate: parent.ddate,
+                qtrs,
+                uom: parent.uom.to_string(),
+                reported,
+                computed,
+                tolerance,
+                missing_children: missing.join(";"),
+            });
+        }
+    }
+
+    result
+}
+
+/// Half a unit of the last reported digit, e.g. 500,000 for values rounded to millions
+fn rounding_tolerance(num: &FsNum) -> f64 {
+    match num.dcml {
+        Some(dcml) if dcml >= INFINITE_DECIMALS => 0.0,
+        Some(dcml) => 0.5 * 10f64.powi(-(dcml as i32)),
+        None => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{
+        cal_line, fs_filing, num_line, parse_tsv, SubLine, CAL_HEADER,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_validates_calculations() {
+        let mut filing = fs_filing(
+            SubLine::new("a", 1, "10-K"),
+            &[
+                num_line("a", "GrossProfit", "20231231", 4, "USD", 40e6),
+                num_line("a", "Revenues", "20231231", 4, "USD", 100e6),
+                num_line("a", "CostOfRevenue", "20231231", 4, "USD", 60e6),
+                num_line("a", "GrossProfit", "20221231", 4, "USD", 45e6),
+                num_line("a", "Revenues", "20221231", 4, "USD", 90e6),
+                num_line("a", "CostOfRevenue", "20221231", 4, "USD", 50e6),
+                num_line("a", "Assets", "20231231", 0, "USD", 500e6),
+                num_line("a", "AssetsCurrent", "20231231", 0, "USD", 200e6),
+            ],
+            &[],
+            &[],
+        );
+        filing.cals = parse_tsv(
+            CAL_HEADER,
+            &[
+                cal_line("a", 1, 1, 0, "GrossProfit", "Revenues"),
+                cal_line("a", 1, 2, 1, "GrossProfit", "CostOfRevenue"),
+                cal_line("a", 2, 1, 0, "Assets", "AssetsCurrent"),
+                cal_line("a", 2, 2, 0, "Assets", "AssetsNoncurrent"),
+            ],
+        );
+
+        let inconsistencies = validate_calculations(&filing);
+
+        assert_eq!(inconsistencies.len(), 2);
+        let gross_profit = &inconsistencies[0];
+        assert_eq!(gross_profit.ptag, "GrossProfit");
+        assert_eq!(
+            gross_profit.ddate,
+            NaiveDate::from_ymd_opt(2022, 12, 31).unwrap()
+        );
+        assert_eq!(gross_profit.difference(), 5e6);
+        assert_eq!(gross_profit.tolerance, 1.5e6);
+
+        let assets = &inconsistencies[1];
+        assert_eq!(assets.ptag, "Assets");
+        assert_eq!(assets.missing_children, "AssetsNoncurrent");
+    }
+}
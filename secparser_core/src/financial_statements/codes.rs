@@ -0,0 +1,234 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Defines an enum over a documented code set, falling back to `Other` for unknown codes.
+/// The first code of each variant is used when serializing, the rest are accepted aliases.
+macro_rules! code_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident => $code:literal $(| $alias:literal)*,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $($variant,)+
+            Other(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)+
+                    Self::Other(v) => v,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value.trim() {
+                    $($code $(| $alias)* => Self::$variant,)+
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(|v| Self::from(v.as_str()))
+            }
+        }
+    };
+}
+
+code_enum! {
+    /// Submission type (`FsSub::form`)
+    pub enum FormType {
+        TenK => "10-K",
+        TenKA => "10-K/A",
+        TenKT => "10-KT",
+        TenKTA => "10-KT/A",
+        TenQ => "10-Q",
+        TenQA => "10-Q/A",
+        TenQT => "10-QT",
+        TenQTA => "10-QT/A",
+        TwentyF => "20-F",
+        TwentyFA => "20-F/A",
+        FortyF => "40-F",
+        FortyFA => "40-F/A",
+        SixK => "6-K",
+        SixKA => "6-K/A",
+        EightK => "8-K",
+        EightKA => "8-K/A",
+    }
+}
+
+impl FormType {
+    pub fn is_amendment(&self) -> bool {
+        self.as_str().ends_with("/A")
+    }
+
+    pub fn is_annual(&self) -> bool {
+        matches!(
+            self,
+            Self::TenK
+                | Self::TenKA
+                | Self::TenKT
+                | Self::TenKTA
+                | Self::TwentyF
+                | Self::TwentyFA
+                | Self::FortyF
+                | Self::FortyFA
+        )
+    }
+
+    pub fn is_quarterly(&self) -> bool {
+        matches!(self, Self::TenQ | Self::TenQA | Self::TenQT | Self::TenQTA)
+    }
+}
+
+code_enum! {
+    /// Fiscal period focus (`FsSub::fp`)
+    pub enum FiscalPeriod {
+        FY => "FY",
+        Q1 => "Q1",
+        Q2 => "Q2",
+        Q3 => "Q3",
+        Q4 => "Q4",
+        H1 => "H1",
+        H2 => "H2",
+        M9 => "M9",
+        T1 => "T1",
+        T2 => "T2",
+        T3 => "T3",
+        M8 => "M8",
+        CY => "CY",
+    }
+}
+
+code_enum! {
+    /// Filer status with the SEC at the time of submission (`FsSub::afs`)
+    pub enum FilerStatus {
+        LargeAccelerated => "1-LAF",
+        Accelerated => "2-ACC",
+        SmallerReportingAccelerated => "3-SRA",
+        NonAccelerated => "4-NON",
+        SmallerReporting => "5-SML",
+    }
+}
+
+code_enum! {
+    /// Financial statement location (`FsPre::stmt`)
+    pub enum StatementType {
+        BalanceSheet => "BS",
+        IncomeStatement => "IS",
+        CashFlow => "CF",
+        Equity => "EQ",
+        ComprehensiveIncome => "CI",
+        ScheduleOfInvestments => "SI",
+        Unclassifiable => "UN",
+        CoverPage => "CP",
+    }
+}
+
+code_enum! {
+    /// Whether a tag is a point in time or a duration (`FsTag::iord`)
+    pub enum InstantOrDuration {
+        Instant => "I",
+        Duration => "D",
+    }
+}
+
+code_enum! {
+    /// Natural accounting balance of a monetary tag (`FsTag::crdr`)
+    pub enum CreditDebit {
+        Credit => "C",
+        Debit => "D",
+    }
+}
+
+code_enum! {
+    /// XBRL data type of a tag (`FsTag::datatype`)
+    pub enum DataType {
+        Monetary => "monetary",
+        Shares => "shares",
+        PerShare => "perShare",
+        Pure => "pure",
+        Percent => "percent",
+        Decimal => "decimal",
+        Integer => "integer",
+        NonNegativeInteger => "nonNegativeInteger",
+        Boolean => "boolean",
+        Date => "date",
+        Duration => "duration",
+        String => "string",
+        TextBlock => "textBlock",
+    }
+}
+
+code_enum! {
+    /// Category of a rendered report (`FsRen::menucat`)
+    pub enum MenuCategory {
+        Cover => "C" | "Cover",
+        Statements => "S" | "Statements",
+        Notes => "N" | "Notes",
+        Policies => "P" | "Policies",
+        Tables => "T" | "Tables",
+        Details => "D" | "Details",
+        Uncategorized => "U" | "Uncategorized",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_known_codes() {
+        assert_eq!(FormType::from("10-K"), FormType::TenK);
+        assert_eq!(FormType::from("10-Q/A"), FormType::TenQA);
+        assert!(FormType::TenQA.is_amendment());
+        assert_eq!(StatementType::from("BS"), StatementType::BalanceSheet);
+        assert_eq!(MenuCategory::from("Statements"), MenuCategory::Statements);
+        assert_eq!(MenuCategory::from("S"), MenuCategory::Statements);
+    }
+
+    #[test]
+    fn it_keeps_unknown_codes() {
+        let form = FormType::from("N-CSR");
+        assert_eq!(form, FormType::Other("N-CSR".to_string()));
+        assert_eq!(form.to_string(), "N-CSR");
+        assert!(!form.is_amendment());
+    }
+
+    #[test]
+    fn it_round_trips_through_csv() {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer
+            .serialize((FormType::TenKA, MenuCategory::Notes))
+            .unwrap();
+        let data = writer.into_inner().unwrap();
+        assert_eq!(String::from_utf8(data.clone()).unwrap(), "10-K/A,N\n");
+
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_slice());
+        let record: (FormType, MenuCategory) = reader.deserialize().next().unwrap().unwrap();
+        assert_eq!(record, (FormType::TenKA, MenuCategory::Notes));
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+const DATE_FORMAT: &str = "%Y%m%d";
+const ISO_DATE_FORMAT: &str = "%Y-%m-%d";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"];
+
+fn parse_date<E: de::Error>(value: &str) -> Result<NaiveDate, E> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT)
+        .map_err(|e| E::custom(format!("invalid yyyymmdd date {value:?}: {e}")))
+}
+
+fn parse_datetime<E: de::Error>(value: &str) -> Result<NaiveDateTime, E> {
+    let value = value.trim();
+
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| E::custom(format!("invalid datetime {value:?}")))
+}
+
+fn non_blank(value: String) -> Option<String> {
+    match value.trim().is_empty() {
+        true => None,
+        false => Some(value),
+    }
+}
+
+/// (De)serializes a `yyyymmdd` field as a `NaiveDate`
+pub mod yyyymmdd {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date.format(DATE_FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        parse_date(&String::deserialize(deserializer)?)
+    }
+}
+
+/// (De)serializes a `yyyymmdd` field that may be blank as an `Option<NaiveDate>`
+pub mod yyyymmdd_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        date: &Option<NaiveDate>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.collect_str(&date.format(DATE_FORMAT)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NaiveDate>, D::Error> {
+        non_blank(String::deserialize(deserializer)?)
+            .map(|v| parse_date(&v))
+            .transpose()
+    }
+}
+
+/// (De)serializes a `yyyy-mm-dd` field as a `NaiveDate`, for user-supplied files
+pub mod iso_date {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&date.format(ISO_DATE_FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(value.trim(), ISO_DATE_FORMAT)
+            .map_err(|e| de::Error::custom(format!("invalid yyyy-mm-dd date {value:?}: {e}")))
+    }
+}
+
+/// (De)serializes a `yyyy-mm-dd hh:mm:ss[.f]` field as a `NaiveDateTime`
+pub mod datetime {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        datetime: &NaiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&datetime.format(DATETIME_FORMAT))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NaiveDateTime, D::Error> {
+        parse_datetime(&String::deserialize(deserializer)?)
+    }
+}
+
+/// Fiscal year end as reported in `FsSub::fye`, e.g. `0930` for September 30
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FiscalYearEnd {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl FiscalYearEnd {
+    pub fn new(month: u32, day: u32) -> Option<Self> {
+        // 2000 is a leap year, so Feb 29 is accepted
+        NaiveDate::from_ymd_opt(2000, month, day).map(|_| Self { month, day })
+    }
+
+    /// The fiscal year end falling in the given calendar year
+    pub fn in_year(&self, year: i32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, self.month, self.day).or_else(|| {
+            // Feb 29 fiscal year ends fall back to Feb 28 in non-leap years
+            NaiveDate::from_ymd_opt(year, self.month, self.day - 1)
+        })
+    }
+}
+
+impl fmt::Display for FiscalYearEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}{:02}", self.month, self.day)
+    }
+}
+
+impl Serialize for FiscalYearEnd {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FiscalYearEnd {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let value = value.trim();
+
+        let parsed = match (value.len(), value.get(..2), value.get(2..)) {
+            (4, Some(month), Some(day)) => month
+                .parse()
+                .ok()
+                .zip(day.parse().ok())
+                .and_then(|(month, day)| Self::new(month, day)),
+            _ => None,
+        };
+
+        parsed.ok_or_else(|| de::Error::custom(format!("invalid mmdd fiscal year end {value:?}")))
+    }
+}
+
+/// (De)serializes a `mmdd` field that may be blank as an `Option<FiscalYearEnd>`
+pub mod fiscal_year_end_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        fye: &Option<FiscalYearEnd>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match fye {
+            Some(fye) => serializer.collect_str(fye),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<FiscalYearEnd>, D::Error> {
+        non_blank(String::deserialize(deserializer)?)
+            .map(|v| FiscalYearEnd::deserialize(de::value::StringDeserializer::new(v)))
+            .transpose()
+    }
+}
+
+/// Inclusive date interval covered by a fact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DateInterval {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateInterval {
+    /// Converts `ddate` and `qtrs` into an explicit interval.
+    ///
+    /// `ddate` is the end date of the period rounded to the nearest month end, and `qtrs` is
+    /// the duration in quarters, with 0 meaning a point in time (e.g. balance sheet values).
+    pub fn from_ddate_qtrs(ddate: NaiveDate, qtrs: u16) -> Option<Self> {
+        if qtrs == 0 {
+            return Some(Self {
+                start: ddate,
+                end: ddate,
+            });
+        }
+
+        let months = Months::new(u32::from(qtrs) * 3);
+        let is_month_end = ddate.succ_opt().is_some_and(|d| d.day() == 1);
+        let start = match is_month_end {
+            true => ddate
+                .with_day(1)
+                .and_then(|d| d.checked_sub_months(months))?
+                .checked_add_months(Months::new(1))?,
+            false => ddate.checked_sub_months(months)?.succ_opt()?,
+        };
+
+        Some(Self { start, end: ddate })
+    }
+
+    pub fn is_instant(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::value::{Error, StringDeserializer};
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn it_parses_dates() {
+        let parsed = yyyymmdd::deserialize(StringDeserializer::<Error>::new("20240331".into()));
+        assert_eq!(parsed.unwrap(), date(2024, 3, 31));
+
+        let blank = yyyymmdd_opt::deserialize(StringDeserializer::<Error>::new(" ".into()));
+        assert_eq!(blank.unwrap(), None);
+
+        let invalid = yyyymmdd_opt::deserialize(StringDeserializer::<Error>::new("2024".into()));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn it_parses_accepted_datetime() {
+        let parsed = datetime::deserialize(StringDeserializer::<Error>::new(
+            "2024-01-05 16:30:00.0".into(),
+        ))
+        .unwrap();
+        assert_eq!(parsed, date(2024, 1, 5).and_hms_opt(16, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn it_parses_fiscal_year_end() {
+        let fye = FiscalYearEnd::deserialize(StringDeserializer::<Error>::new("0930".into()));
+        let fye = fye.unwrap();
+        assert_eq!(fye, FiscalYearEnd { month: 9, day: 30 });
+        assert_eq!(fye.to_string(), "0930");
+        assert_eq!(fye.in_year(2023), Some(date(2023, 9, 30)));
+
+        let leap = FiscalYearEnd::new(2, 29).unwrap();
+        assert_eq!(leap.in_year(2023), Some(date(2023, 2, 28)));
+
+        let invalid = FiscalYearEnd::deserialize(StringDeserializer::<Error>::new("1332".into()));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn it_converts_ddate_qtrs_to_interval() {
+        let instant = DateInterval::from_ddate_qtrs(date(2024, 3, 31), 0).unwrap();
+        assert!(instant.is_instant());
+
+        let quarter = DateInterval::from_ddate_qtrs(date(2024, 6, 30), 1).unwrap();
+        assert_eq!(quarter.start, date(2024, 4, 1));
+
+        let year = DateInterval::from_ddate_qtrs(date(2023, 12, 31), 4).unwrap();
+        assert_eq!(year.start, date(2023, 1, 1));
+        assert_eq!(year.days(), 365);
+
+        let mid_month = DateInterval::from_ddate_qtrs(date(2024, 9, 28), 4).unwrap();
+        assert_eq!(mid_month.start, date(2023, 9, 29));
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsDim {
     pub dimhash: String,
     pub segments: String,
@@ -13,6 +13,10 @@ impl FsRecord for FsDim {
     fn csv_filename() -> String {
         "dim.tsv".to_string()
     }
+
+    fn has_adsh() -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
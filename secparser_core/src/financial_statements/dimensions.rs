@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::DownloadConfig;
+use crate::zip_csv_records::CsvConfig;
+
+use super::dim_record::FsDim;
+use super::num_record::FsNum;
+use super::record::{FsRecords, FsRecordsError};
+use super::tag_catalog::TagCatalog;
+
+/// Prefixes of the taxonomies published by the FASB, IFRS Foundation and the SEC
+const STANDARD_PREFIXES: [&str; 12] = [
+    "us-gaap",
+    "ifrs-full",
+    "dei",
+    "srt",
+    "country",
+    "currency",
+    "exch",
+    "invest",
+    "naics",
+    "sic",
+    "stpr",
+    "ecd",
+];
+
+/// Suffixes DERA may truncate from axis and member names
+const NAME_SUFFIXES: [&str; 3] = ["Axis", "Member", "Domain"];
+
+/// Axis or member of a dimension.
+///
+/// DERA `segments` strip namespace prefixes and may truncate names, e.g. `ProductOrService`
+/// for `us-gaap:ProductOrServiceAxis`, so `prefix` is only set for names given by the caller.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DimensionTag {
+    pub prefix: Option<String>,
+    pub name: String,
+}
+
+impl DimensionTag {
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+
+        match value.split_once(':') {
+            Some((prefix, name)) => Self {
+                prefix: Some(prefix.to_string()),
+                name: name.to_string(),
+            },
+            None => Self {
+                prefix: None,
+                name: value.to_string(),
+            },
+        }
+    }
+
+    /// Whether the tag is defined by the filer rather than a standard taxonomy.
+    ///
+    /// Known from the `custom` flag of the tag in `tag.tsv`, looked up with and without the
+    /// suffixes DERA truncates, or else from the prefix. `None` if neither is known.
+    pub fn is_custom(&self, catalog: &TagCatalog) -> Option<bool> {
+        let from_catalog = std::iter::once(self.name.clone())
+            .chain(NAME_SUFFIXES.map(|suffix| format!("{}{suffix}", self.name)))
+            .find_map(|name| {
+                let mut versions = catalog.versions(&name).peekable();
+                versions.peek()?;
+                Some(versions.all(|t| t.is_custom()))
+            });
+
+        from_catalog.or_else(|| {
+            let prefix = self.prefix.as_deref()?;
+            Some(!STANDARD_PREFIXES.contains(&prefix))
+        })
+    }
+
+    /// Whether the names match, ignoring prefixes and the `Statement` prefix and
+    /// `Axis`/`Member`/`Domain` suffixes that DERA may truncate
+    pub fn matches(&self, name: &str) -> bool {
+        let name = DimensionTag::parse(name);
+        normalize(&self.name) == normalize(&name.name)
+    }
+}
+
+impl fmt::Display for DimensionTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.prefix {
+            Some(prefix) => write!(f, "{}:{}", prefix, self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+fn normalize(name: &str) -> &str {
+    let name = name.strip_prefix("Statement").unwrap_or(name);
+    NAME_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
+/// Parsed `FsDim::segments`, axis/member pairs in the order they appear
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dimensions {
+    pub pairs: Vec<(DimensionTag, DimensionTag)>,
+}
+
+impl Dimensions {
+    pub fn parse(segments: &str) -> Self {
+        let pairs = segments
+            .split(';')
+            .filter_map(|segment| segment.split_once('='))
+            .map(|(axis, member)| (DimensionTag::parse(axis), DimensionTag::parse(member)))
+            .collect();
+
+        Self { pairs }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn axes(&self) -> impl Iterator<Item = &DimensionTag> {
+        self.pairs.iter().map(|(axis, _)| axis)
+    }
+
+    pub fn member(&self, axis: &str) -> Option<&DimensionTag> {
+        self.pairs
+            .iter()
+            .find(|(a, _)| a.matches(axis))
+            .map(|(_, member)| member)
+    }
+
+    pub fn has_axis(&self, axis: &str) -> bool {
+        self.member(axis).is_some()
+    }
+
+    /// Whether any axis or member is known to be custom
+    pub fn has_custom(&self, catalog: &TagCatalog) -> bool {
+        self.pairs.iter().any(|(axis, member)| {
+            axis.is_custom(catalog) == Some(true) || member.is_custom(catalog) == Some(true)
+        })
+    }
+}
+
+impl FromStr for Dimensions {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (axis, member) in &self.pairs {
+            write!(f, "{axis}={member};")?;
+        }
+        Ok(())
+    }
+}
+
+impl FsDim {
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions::parse(&self.segments)
+    }
+}
+
+/// Lookup from `FsNum::dimh` to parsed dimensions
+#[derive(Debug, Default)]
+pub struct DimensionIndex {
+    dimensions: HashMap<String, Dimensions>,
+}
+
+impl DimensionIndex {
+    pub fn new(
+        download_config: &DownloadConfig,
+        csv_config: CsvConfig,
+        from_year: i32,
+    ) -> Result<Self, FsRecordsError> {
+        let records: FsRecords<FsDim> = FsRecords::new(download_config, csv_config, from_year)?;
+
+        Ok(records.collect())
+    }
+
+    pub fn get(&self, dimh: &str) -> Option<&Dimensions> {
+        self.dimensions.get(dimh)
+    }
+
+    pub fn len(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dimensions.is_empty()
+    }
+}
+
+impl FromIterator<FsDim> for DimensionIndex {
+    fn from_iter<I: IntoIterator<Item = FsDim>>(iter: I) -> Self {
+        let dimensions = iter
+            .into_iter()
+            .map(|dim| {
+                let dimensions = dim.dimensions();
+                (dim.dimhash, dimensions)
+            })
+            .collect();
+
+        Self { dimensions }
+    }
+}
+
+/// Dimension filters on streams of `FsNum`
+pub trait FsNumDimensionsExt: Iterator<Item = FsNum> + Sized {
+    /// Keeps values without any dimension (`dimn == 0`)
+    fn consolidated_only(self) -> impl Iterator<Item = FsNum> {
+        self.filter(|num| num.dimn == Some(0))
+    }
+
+    /// Keeps values segmented by the given axis, e.g. `ProductOrServiceAxis`
+    fn with_axis<'a>(
+        self,
+        index: &'a DimensionIndex,
+        axis: &'a str,
+    ) -> impl Iterator<Item = FsNum> + 'a
+    where
+        Self: 'a,
+    {
+        self.filter(move |num| index.get(&num.dimh).is_some_and(|d| d.has_axis(axis)))
+    }
+
+    /// Keeps values for the given member of an axis
+    fn with_member<'a>(
+        self,
+        index: &'a DimensionIndex,
+        axis: &'a str,
+        member: &'a str,
+    ) -> impl Iterator<Item = FsNum> + 'a
+    where
+        Self: 'a,
+    {
+        self.filter(move |num| {
+            index
+                .get(&num.dimh)
+                .and_then(|d| d.member(axis))
+                .is_some_and(|m| m.matches(member))
+        })
+    }
+}
+
+impl<I: Iterator<Item = FsNum>> FsNumDimensionsExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::tag_record::FsTag;
+    use crate::financial_statements::test_utils::{
+        num_line_with_dim, parse_tsv, NUM_HEADER, TAG_HEADER,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_parses_segments() {
+        let dimensions = Dimensions::parse("Geographical=Americas;ProductOrService=IPhone;");
+
+        assert_eq!(dimensions.len(), 2);
+        assert_eq!(dimensions.pairs[0].0.prefix, None);
+        assert!(dimensions.has_axis("ProductOrServiceAxis"));
+        assert_eq!(
+            dimensions
+                .member("us-gaap:ProductOrServiceAxis")
+                .unwrap()
+                .name,
+            "IPhone"
+        );
+        assert!(dimensions
+            .member("srt:StatementGeographicalAxis")
+            .is_some_and(|m| m.matches("AmericasMember")));
+        assert_eq!(
+            dimensions.to_string(),
+            "Geographical=Americas;ProductOrService=IPhone;"
+        );
+    }
+
+    #[test]
+    fn it_classifies_custom_members() {
+        let catalog = parse_tsv::<FsTag>(
+            TAG_HEADER,
+            &[
+                "AmericasMember\tsrt/2023\t0\t0\tmember\t\t\tAmericas\t".to_string(),
+                "IPhoneMember\t0000320193-23-000106\t1\t0\tmember\t\t\tiPhone\t".to_string(),
+            ],
+        )
+        .into_iter()
+        .collect::<TagCatalog>();
+        let dimensions = Dimensions::parse("Geographical=Americas;ProductOrService=IPhone;");
+
+        assert_eq!(dimensions.pairs[0].1.is_custom(&catalog), Some(false));
+        assert_eq!(dimensions.pairs[1].1.is_custom(&catalog), Some(true));
+        assert_eq!(dimensions.pairs[0].0.is_custom(&catalog), None);
+        assert!(dimensions.has_custom(&catalog));
+
+        let empty = TagCatalog::default();
+        assert_eq!(
+            DimensionTag::parse("aapl:Custom").is_custom(&empty),
+            Some(true)
+        );
+        assert_eq!(
+            DimensionTag::parse("us-gaap:Other").is_custom(&empty),
+            Some(false)
+        );
+        assert!(!Dimensions::parse("ProductOrService=IPhone;").has_custom(&empty));
+    }
+
+    #[test]
+    fn it_filters_nums_by_dimensions() {
+        let index = parse_tsv::<FsDim>(
+            "dimhash\tsegments\tsegt",
+            &[
+                "0x00000000\t\t0".to_string(),
+                "0x1\tProductOrService=iPhone;\t0".to_string(),
+                "0x2\tProductOrService=Mac;\t0".to_string(),
+                "0x3\tSegments=Americas;\t0".to_string(),
+            ],
+        )
+        .into_iter()
+        .collect::<DimensionIndex>();
+        let nums = parse_tsv::<FsNum>(
+            NUM_HEADER,
+            &[
+                num_line_with_dim("a", "Revenues", "20231231", 4, "USD", 10.0, "0x00000000", 0),
+                num_line_with_dim("a", "Revenues", "20231231", 4, "USD", 6.0, "0x1", 1),
+                num_line_with_dim("a", "Revenues", "20231231", 4, "USD", 4.0, "0x2", 1),
+                num_line_with_dim("a", "Revenues", "20231231", 4, "USD", 7.0, "0x3", 1),
+            ],
+        );
+
+        assert_eq!(index.len(), 4);
+        assert_eq!(nums.clone().into_iter().consolidated_only().count(), 1);
+
+        let by_product = nums
+            .clone()
+            .into_iter()
+            .with_axis(&index, "ProductOrServiceAxis")
+            .filter_map(|n| n.value)
+            .collect::<Vec<_>>();
+        assert_eq!(by_product, vec![6.0, 4.0]);
+
+        let mac = nums
+            .into_iter()
+            .with_member(&index, "ProductOrServiceAxis", "MacMember")
+            .count();
+        assert_eq!(mac, 1);
+    }
+}
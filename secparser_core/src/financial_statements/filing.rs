@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::vec;
+
+use snafu::ResultExt;
+
+use crate::data_source::DataSource;
+use crate::downloader::DownloadConfig;
+use crate::zip_csv_records::{CsvConfig, ZipCsvRecords, ZipCsvRecordsError};
+
+use super::cal_record::FsCal;
+use super::data_source::FsDataSources;
+use super::filter::FsFilter;
+use super::num_record::FsNum;
+use super::pre_record::FsPre;
+use super::record::{DataSourceSnafu, FsRecord, FsRecordsError};
+use super::ren_record::FsRen;
+use super::sub_record::FsSub;
+use super::txt_record::FsTxt;
+
+/// A submission together with all of its rows in the other tables
+#[derive(Debug)]
+pub struct FsFiling {
+    pub sub: FsSub,
+    pub nums: Vec<FsNum>,
+    pub txts: Vec<FsTxt>,
+    pub pres: Vec<FsPre>,
+    pub rens: Vec<FsRen>,
+    pub cals: Vec<FsCal>,
+}
+
+/// Iterates over filings, archive by archive.
+///
+/// Submissions of an archive are processed in batches of `batch_size`: each child table is
+/// read once per batch and grouped by `adsh`, so only the child rows of one batch are held in
+/// memory at a time.
+///
+/// An archive that fails to read panics if `CsvConfig::panic_on_error` is set, and is
+/// otherwise logged and skipped.
+pub struct FsFilings {
+    pub config: CsvConfig,
+    pub filter: FsFilter,
+    pub batch_size: usize,
+    data_source_iter: vec::IntoIter<DataSource>,
+    data_source: Option<DataSource>,
+    subs: VecDeque<FsSub>,
+    filings: VecDeque<FsFiling>,
+}
+
+impl FsFilings {
+    pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+    pub fn new(
+        download_config: &DownloadConfig,
+        csv_config: CsvConfig,
+        from_year: i32,
+        filter: FsFilter,
+    ) -> Result<Self, FsRecordsError> {
+        let data_sources =
+            FsDataSources::new(download_config, from_year).context(DataSourceSnafu)?;
+
+        Ok(Self::from_data_sources(
+            data_sources.vec,
+            csv_config,
+            filter,
+        ))
+    }
+
+    pub fn from_data_sources(
+        data_sources: Vec<DataSource>,
+        csv_config: CsvConfig,
+        filter: FsFilter,
+    ) -> Self {
+        Self {
+            config: csv_config,
+            filter,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            data_source_iter: data_sources.into_iter(),
+            data_source: None,
+            subs: VecDeque::new(),
+            filings: VecDeque::new(),
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    fn load_next_data_source(&mut self) -> Result<bool, ZipCsvRecordsError> {
+        self.data_source = self.data_source_iter.next();
+
+        match &self.data_source {
+            Some(data_source) => {
+                log::info!("Processing filings in {}", data_source.filepath.display());
+                let subs: ZipCsvRecords<FsSub> = ZipCsvRecords::new_with_filter(
+                    data_source,
+                    &self.config,
+                    &FsSub::csv_filename(),
+                    &self.filter.sub_filter(),
+                )?;
+                self.subs = subs.collect();
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn load_next_batch(&mut self) -> Result<(), ZipCsvRecordsError> {
+        let Some(data_source) = &self.data_source else {
+            return Ok(());
+        };
+
+        let count = self.batch_size.min(self.subs.len());
+        let subs = self.subs.drain(..count).collect::<Vec<_>>();
+        let adshs = subs.iter().map(|s| s.adsh.to_string()).collect();
+
+        let mut nums = self.get_children(data_source, &adshs, |r: &FsNum| &r.adsh)?;
+        let mut txts = self.get_children(data_source, &adshs, |r: &FsTxt| &r.adsh)?;
+        let mut pres = self.get_children(data_source, &adshs, |r: &FsPre| &r.adsh)?;
+        let mut rens = self.get_children(data_source, &adshs, |r: &FsRen| &r.adsh)?;
+        let mut cals = self.get_children(data_source, &adshs, |r: &FsCal| &r.adsh)?;
+
+        self.filings.extend(subs.into_iter().map(|sub| FsFiling {
+            nums: nums.remove(&sub.adsh).unwrap_or_default(),
+            txts: txts.remove(&sub.adsh).unwrap_or_default(),
+            pres: pres.remove(&sub.adsh).unwrap_or_default(),
+            rens: rens.remove(&sub.adsh).unwrap_or_default(),
+            cals: cals.remove(&sub.adsh).unwrap_or_default(),
+            sub,
+        }));
+
+        Ok(())
+    }
+
+    /// Panics or logs the error of the current archive, and skips its remaining submissions
+    fn on_error(&mut self, e: ZipCsvRecordsError) {
+        let filepath = self.data_source.as_ref().map(|d| d.filepath.display());
+        if self.config.panic_on_error {
+            panic!("Should get filings from {filepath:?}: {e}");
+        }
+
+        log::warn!("Skipping filings of {filepath:?}: {e}");
+        self.subs.clear();
+    }
+
+    /// Rows of a child table for the submissions in `adshs`, grouped by `adsh`
+    fn get_children<T, F>(
+        &self,
+        data_source: &DataSource,
+        adshs: &HashSet<String>,
+        get_adsh: F,
+    ) -> Result<HashMap<String, Vec<T>>, ZipCsvRecordsError>
+    where
+        T: FsRecord,
+        F: Fn(&T) -> &String,
+    {
+        let records: ZipCsvRecords<T> = ZipCsvRecords::new_with_filter(
+            data_source,
+            &self.config,
+            &T::csv_filename(),
+            &self.filter.record_filter(Some(adshs)),
+        )?;
+
+        let mut result: HashMap<String, Vec<T>> = HashMap::new();
+        for record in records {
+            result
+                .entry(get_adsh(&record).to_string())
+                .or_default()
+                .push(record);
+        }
+
+        Ok(result)
+    }
+}
+
+impl Iterator for FsFilings {
+    type Item = FsFiling;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(filing) = self.filings.pop_front() {
+                return Some(filing);
+            }
+
+            if !self.subs.is_empty() {
+                if let Err(e) = self.load_next_batch() {
+                    self.on_error(e);
+                }
+                continue;
+            }
+
+            match self.load_next_data_source() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => self.on_error(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::codes::FormType;
+    use crate::financial_statements::filter::FsFilterBuilder;
+    use crate::financial_statements::test_utils::{
+        fs_archive, num_line, sub_line, SubLine, SUB_HEADER,
+    };
+    use crate::zip_csv_records::{write_test_zip, CsvConfigBuilder};
+
+    use super::*;
+
+    #[test]
+    fn it_groups_rows_by_submission() {
+        let data_source = fs_archive(
+            "secparser_fs_filings.zip",
+            &[
+                sub_line(SubLine::new("a", 1, "10-K")),
+                sub_line(SubLine::new("b", 2, "10-Q")),
+                sub_line(SubLine::new("c", 3, "10-K")),
+            ],
+            &[
+                num_line("a", "Revenues", "20231231", 4, "USD", 10.0),
+                num_line("b", "Revenues", "20231231", 1, "USD", 20.0),
+                num_line("c", "Revenues", "20231231", 4, "USD", 30.0),
+                num_line("a", "Assets", "20231231", 0, "USD", 40.0),
+            ],
+            &[],
+        );
+        let config = CsvConfigBuilder::default()
+            .panic_on_error(true)
+            .build()
+            .unwrap();
+        let filter = FsFilterBuilder::default()
+            .forms([FormType::TenK])
+            .build()
+            .unwrap();
+
+        let filings = FsFilings::from_data_sources(vec![data_source], config, filter)
+            .batch_size(1)
+            .collect::<Vec<_>>();
+
+        assert_eq!(filings.len(), 2);
+        assert_eq!(filings[0].sub.adsh, "a");
+        assert_eq!(filings[0].nums.len(), 2);
+        assert_eq!(filings[1].sub.adsh, "c");
+        assert_eq!(filings[1].nums.len(), 1);
+        assert!(filings[1].pres.is_empty());
+    }
+
+    #[test]
+    fn it_skips_unreadable_archives() {
+        let broken = write_test_zip(
+            "secparser_fs_filings_broken.zip",
+            &[(
+                "sub.tsv",
+                &format!("{SUB_HEADER}\n{}\n", sub_line(SubLine::new("x", 9, "10-K"))),
+            )],
+        );
+        let data_source = fs_archive(
+            "secparser_fs_filings_valid.zip",
+            &[sub_line(SubLine::new("a", 1, "10-K"))],
+            &[num_line("a", "Revenues", "20231231", 4, "USD", 10.0)],
+            &[],
+        );
+        let config = CsvConfigBuilder::default().build().unwrap();
+
+        let filings =
+            FsFilings::from_data_sources(vec![broken, data_source], config, FsFilter::default())
+                .collect::<Vec<_>>();
+
+        assert_eq!(filings.len(), 1);
+        assert_eq!(filings[0].sub.adsh, "a");
+        assert_eq!(filings[0].nums.len(), 1);
+    }
+}
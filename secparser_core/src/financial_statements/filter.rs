@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use derive_builder::Builder;
+
+use crate::zip_csv_records::RecordFilter;
+
+use super::codes::FormType;
+
+/// Filters applied to raw rows before deserialization.
+///
+/// `ciks`, `forms` and `fiscal_years` are columns of `sub.tsv` only. For the other tables they
+/// are turned into a set of `adsh` by reading the filtered `sub.tsv` of the same archive.
+/// Tables without an `adsh` column (`tag.tsv`, `dim.tsv`) are not filtered by submission, and
+/// `tags` and `uoms` only apply to tables with a `tag` or `uom` column.
+#[derive(Clone, Debug, Default, Builder)]
+#[builder(default)]
+pub struct FsFilter {
+    #[builder(setter(custom))]
+    pub ciks: Option<HashSet<usize>>,
+    #[builder(setter(custom))]
+    pub adshs: Option<HashSet<String>>,
+    #[builder(setter(custom))]
+    pub forms: Option<HashSet<FormType>>,
+    #[builder(setter(custom))]
+    pub tags: Option<HashSet<String>>,
+    #[builder(setter(custom))]
+    pub fiscal_years: Option<HashSet<i32>>,
+    #[builder(setter(custom))]
+    pub uoms: Option<HashSet<String>>,
+}
+
+impl FsFilterBuilder {
+    pub fn ciks(&mut self, ciks: impl IntoIterator<Item = usize>) -> &mut Self {
+        self.ciks = Some(Some(ciks.into_iter().collect()));
+        self
+    }
+
+    pub fn adshs<S: Into<String>>(&mut self, adshs: impl IntoIterator<Item = S>) -> &mut Self {
+        self.adshs = Some(Some(adshs.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    pub fn forms(&mut self, forms: impl IntoIterator<Item = FormType>) -> &mut Self {
+        self.forms = Some(Some(forms.into_iter().collect()));
+        self
+    }
+
+    pub fn tags<S: Into<String>>(&mut self, tags: impl IntoIterator<Item = S>) -> &mut Self {
+        self.tags = Some(Some(tags.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    pub fn fiscal_years(&mut self, fiscal_years: impl IntoIterator<Item = i32>) -> &mut Self {
+        self.fiscal_years = Some(Some(fiscal_years.into_iter().collect()));
+        self
+    }
+
+    pub fn uoms<S: Into<String>>(&mut self, uoms: impl IntoIterator<Item = S>) -> &mut Self {
+        self.uoms = Some(Some(uoms.into_iter().map(Into::into).collect()));
+        self
+    }
+}
+
+impl FsFilter {
+    /// Whether the filter has predicates that only `sub.tsv` can answer
+    pub fn has_sub_predicates(&self) -> bool {
+        self.ciks.is_some() || self.forms.is_some() || self.fiscal_years.is_some()
+    }
+
+    /// Filter for `sub.tsv`
+    pub fn sub_filter(&self) -> RecordFilter {
+        let mut filter = RecordFilter::default();
+
+        if let Some(ciks) = &self.ciks {
+            filter = filter.column("cik", ciks.iter().map(|v| v.to_string()));
+        }
+        if let Some(forms) = &self.forms {
+            filter = filter.column("form", forms.iter().map(|v| v.to_string()));
+        }
+        if let Some(fiscal_years) = &self.fiscal_years {
+            filter = filter.column("fy", fiscal_years.iter().map(|v| v.to_string()));
+        }
+        if let Some(adshs) = &self.adshs {
+            filter = filter.column("adsh", adshs.iter().cloned());
+        }
+
+        filter
+    }
+
+    /// Filter for tables other than `sub.tsv`, given the `adsh` set to keep, e.g. derived from
+    /// `sub.tsv`. Pass `None` for tables without an `adsh` column.
+    pub fn record_filter(&self, adshs: Option<&HashSet<String>>) -> RecordFilter {
+        let mut filter = RecordFilter::default();
+
+        if let Some(adshs) = adshs {
+            filter = filter.column("adsh", adshs.iter().cloned());
+        }
+        if let Some(tags) = &self.tags {
+            filter = filter.optional_column("tag", tags.iter().cloned());
+        }
+        if let Some(uoms) = &self.uoms {
+            filter = filter.optional_column("uom", uoms.iter().cloned());
+        }
+
+        filter
+    }
+}
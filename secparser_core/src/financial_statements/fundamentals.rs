@@ -0,0 +1,365 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use snafu::{Location, ResultExt, Snafu};
+
+use super::codes::{FiscalPeriod, FormType};
+use super::date;
+use super::filing::FsFiling;
+use super::num_record::FsNum;
+use super::statement::NO_DIMENSIONS_HASH;
+use super::unit::UnitKind;
+
+const DEFAULT_MAPPING: &str = include_str!("../../data/us_gaap_fundamentals.json");
+
+#[derive(Debug, Snafu)]
+pub enum FundamentalsError {
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to deserialize {filepath:?}"))]
+    Deserialize {
+        source: serde_json::Error,
+        filepath: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Concept {
+    Revenue,
+    NetIncome,
+    TotalAssets,
+    Equity,
+    OperatingCashFlow,
+    SharesOutstanding,
+    EpsBasic,
+    EpsDiluted,
+}
+
+impl Concept {
+    pub fn is_instant(&self) -> bool {
+        matches!(
+            self,
+            Self::TotalAssets | Self::Equity | Self::SharesOutstanding
+        )
+    }
+}
+
+/// Tags of a concept, in order of preference, and the kind of unit its values are reported in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConceptTags {
+    pub kind: UnitKind,
+    pub tags: Vec<String>,
+}
+
+/// Tags mapped to each concept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConceptMapping {
+    pub concepts: BTreeMap<Concept, ConceptTags>,
+}
+
+impl Default for ConceptMapping {
+    /// The us-gaap mapping shipped in `data/us_gaap_fundamentals.json`
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_MAPPING)
+            .unwrap_or_else(|e| panic!("Should parse default concept mapping: {e}"))
+    }
+}
+
+impl ConceptMapping {
+    pub fn from_path(filepath: &Path) -> Result<Self, FundamentalsError> {
+        let reader = BufReader::new(File::open(filepath)?);
+
+        serde_json::from_reader(reader).context(DeserializeSnafu { filepath })
+    }
+
+    /// Normalized values for the period of the filing.
+    ///
+    /// Duration concepts use the fiscal year for annual filings and the quarter otherwise,
+    /// falling back to the longest duration ending on the period (e.g. year-to-date cash
+    /// flows in a 10-Q). Instant concepts use the period end, falling back to the closest
+    /// later date (e.g. shares outstanding as of the cover page date). Monetary values are
+    /// taken in the reporting currency of the filing only, and are unscaled.
+    pub fn fundamentals(&self, filing: &FsFiling) -> Fundamentals {
+        let sub = &filing.sub;
+        let mut result = Fundamentals {
+            adsh: sub.adsh.to_string(),
+            cik: sub.cik,
+            form: sub.form.clone(),
+            fy: sub.fy,
+            fp: sub.fp.clone(),
+            period: sub.period,
+            currency: None,
+            revenue: None,
+            net_income: None,
+            total_assets: None,
+            equity: None,
+            operating_cash_flow: None,
+            shares_outstanding: None,
+            eps_basic: None,
+            eps_diluted: None,
+            sources: BTreeMap::new(),
+        };
+
+        let nums = filing
+            .nums
+            .iter()
+            .filter(|n| n.dimh == NO_DIMENSIONS_HASH && n.coreg.is_empty() && n.value.is_some())
+            .collect::<Vec<_>>();
+        let currency = Self::reporting_currency(&nums);
+        let expected_qtrs = match sub.form.is_annual() || sub.fp == Some(FiscalPeriod::FY) {
+            true => 4,
+            false => 1,
+        };
+
+        for (concept, ConceptTags { kind, tags }) in &self.concepts {
+            let found = tags.iter().find_map(|tag| {
+                let candidates = nums.iter().filter(|n| {
+                    let unit = n.unit();
+                    &n.tag == tag
+                        && unit.kind() == *kind
+                        && unit
+                            .currency()
+                            .is_none_or(|c| Some(c) == currency.as_deref())
+                });
+                match concept.is_instant() {
+                    true => Self::find_instant(candidates, sub.period),
+                    false => Self::find_duration(candidates, sub.period, expected_qtrs),
+                }
+            });
+
+            if let Some(num) = found {
+                result.set(*concept, num.unscaled().value);
+                result.sources.insert(*concept, num.tag.to_string());
+            }
+        }
+        result.currency = currency;
+
+        result
+    }
+
+    /// The currency most values without dimensions are reported in
+    fn reporting_currency(nums: &[&FsNum]) -> Option<String> {
+        let mut counts = HashMap::<String, usize>::new();
+        for num in nums {
+            if let Some(currency) = num.unit().currency() {
+                *counts.entry(currency.to_string()).or_default() += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+            .map(|(currency, _)| currency)
+    }
+
+    fn find_instant<'a>(
+        candidates: impl Iterator<Item = &'a &'a FsNum>,
+        period: NaiveDate,
+    ) -> Option<&'a FsNum> {
+        candidates
+            .filter(|n| n.qtrs == Some(0) && n.ddate >= period)
+            .min_by_key(|n| n.ddate)
+            .copied()
+    }
+
+    fn find_duration<'a>(
+        candidates: impl Iterator<Item = &'a &'a FsNum>,
+        period: NaiveDate,
+        expected_qtrs: u16,
+    ) -> Option<&'a FsNum> {
+        candidates
+            .filter(|n| n.ddate == period && n.qtrs.is_some_and(|q| q > 0))
+            .max_by_key(|n| (n.qtrs == Some(expected_qtrs), n.qtrs))
+            .copied()
+    }
+}
+
+/// Normalized values of a filing for its reporting period
+#[derive(Debug, Clone, Serialize)]
+pub struct Fundamentals {
+    pub adsh: String,
+    pub cik: usize,
+    pub form: FormType,
+    pub fy: Option<i32>,
+    pub fp: Option<FiscalPeriod>,
+    #[serde(with = "date::yyyymmdd")]
+    pub period: NaiveDate,
+    /// Currency of the monetary values
+    pub currency: Option<String>,
+    pub revenue: Option<f64>,
+    pub net_income: Option<f64>,
+    pub total_assets: Option<f64>,
+    pub equity: Option<f64>,
+    pub operating_cash_flow: Option<f64>,
+    pub shares_outstanding: Option<f64>,
+    pub eps_basic: Option<f64>,
+    pub eps_diluted: Option<f64>,
+    /// Tag each value was taken from
+    #[serde(skip)]
+    pub sources: BTreeMap<Concept, String>,
+}
+
+impl Fundamentals {
+    pub fn get(&self, concept: Concept) -> Option<f64> {
+        match concept {
+            Concept::Revenue => self.revenue,
+            Concept::NetIncome => self.net_income,
+            Concept::TotalAssets => self.total_assets,
+            Concept::Equity => self.equity,
+            Concept::OperatingCashFlow => self.operating_cash_flow,
+            Concept::SharesOutstanding => self.shares_outstanding,
+            Concept::EpsBasic => self.eps_basic,
+            Concept::EpsDiluted => self.eps_diluted,
+        }
+    }
+
+    fn set(&mut self, concept: Concept, value: Option<f64>) {
+        let field = match concept {
+            Concept::Revenue => &mut self.revenue,
+            Concept::NetIncome => &mut self.net_income,
+            Concept::TotalAssets => &mut self.total_assets,
+            Concept::Equity => &mut self.equity,
+            Concept::OperatingCashFlow => &mut self.operating_cash_flow,
+            Concept::SharesOutstanding => &mut self.shares_outstanding,
+            Concept::EpsBasic => &mut self.eps_basic,
+            Concept::EpsDiluted => &mut self.eps_diluted,
+        };
+        *field = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{fs_filing, num_line, SubLine};
+
+    use super::*;
+
+    #[test]
+    fn it_maps_tag_variations() {
+        let mapping = ConceptMapping::default();
+        assert_eq!(mapping.concepts.len(), 8);
+
+        let mut sub = SubLine::new("a", 1, "10-Q");
+        sub.period = "20240630";
+        sub.fp = "Q2";
+        let filing = fs_filing(
+            sub,
+            &[
+                num_line("a", "SalesRevenueNet", "20240630", 1, "USD", 100.0),
+                num_line("a", "SalesRevenueNet", "20240630", 2, "USD", 180.0),
+                num_line("a", "Revenues", "20240630", 1, "USD", 99.0),
+                num_line(
+                    "a",
+                    "RevenueFromContractWithCustomerExcludingAssessedTax",
+                    "20240630",
+                    1,
+                    "EUR",
+                    90.0,
+                ),
+                num_line(
+                    "a",
+                    "NetCashProvidedByUsedInOperatingActivities",
+                    "20240630",
+                    2,
+                    "USD",
+                    30.0,
+                ),
+                num_line("a", "Assets", "20240630", 0, "USD", 500.0),
+                num_line("a", "Assets", "20231231", 0, "USD", 450.0),
+                num_line(
+                    "a",
+                    "EntityCommonStockSharesOutstanding",
+                    "20240725",
+                    0,
+                    "shares",
+                    10.0,
+                ),
+                num_line(
+                    "a",
+                    "CommonStockSharesOutstanding",
+                    "20240630",
+                    0,
+                    "USD",
+                    7.0,
+                ),
+                num_line(
+                    "a",
+                    "EarningsPerShareBasicAndDiluted",
+                    "20240630",
+                    1,
+                    "USD/shares",
+                    1.5,
+                ),
+            ],
+            &[],
+            &[],
+        );
+
+        let fundamentals = mapping.fundamentals(&filing);
+
+        assert_eq!(fundamentals.revenue, Some(99.0));
+        assert_eq!(fundamentals.sources[&Concept::Revenue], "Revenues");
+        assert_eq!(fundamentals.operating_cash_flow, Some(30.0));
+        assert_eq!(fundamentals.total_assets, Some(500.0));
+        assert_eq!(fundamentals.shares_outstanding, Some(10.0));
+        assert_eq!(fundamentals.eps_basic, Some(1.5));
+        assert_eq!(fundamentals.eps_diluted, Some(1.5));
+        assert_eq!(fundamentals.net_income, None);
+        assert_eq!(fundamentals.currency.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn it_uses_the_reporting_currency() {
+        let mut sub = SubLine::new("a", 1, "20-F");
+        sub.period = "20241231";
+        sub.fp = "FY";
+        let filing = fs_filing(
+            sub,
+            &[
+                num_line("a", "Revenues", "20241231", 4, "EUR_millions", 2.5),
+                num_line("a", "Revenues", "20241231", 4, "USD", 2.7e6),
+                num_line("a", "NetIncomeLoss", "20241231", 4, "EUR", 4e5),
+                num_line("a", "Assets", "20241231", 0, "EUR", 9e6),
+                num_line(
+                    "a",
+                    "EarningsPerShareBasic",
+                    "20241231",
+                    4,
+                    "EUR/shares",
+                    0.4,
+                ),
+                num_line(
+                    "a",
+                    "EarningsPerShareDiluted",
+                    "20241231",
+                    4,
+                    "USD/shares",
+                    0.43,
+                ),
+            ],
+            &[],
+            &[],
+        );
+
+        let fundamentals = ConceptMapping::default().fundamentals(&filing);
+
+        assert_eq!(fundamentals.currency.as_deref(), Some("EUR"));
+        assert_eq!(fundamentals.revenue, Some(2.5e6));
+        assert_eq!(fundamentals.net_income, Some(4e5));
+        assert_eq!(fundamentals.total_assets, Some(9e6));
+        assert_eq!(fundamentals.eps_basic, Some(0.4));
+        assert_eq!(fundamentals.eps_diluted, None);
+    }
+}
@@ -1,10 +1,27 @@
+pub mod amendments;
+pub mod as_of;
 pub mod cal_record;
+pub mod calculation;
+pub mod codes;
 pub mod data_source;
+pub mod date;
 pub mod dim_record;
+pub mod dimensions;
+pub mod filing;
+pub mod filter;
+pub mod fundamentals;
 pub mod num_record;
 pub mod pre_record;
 pub mod record;
 pub mod ren_record;
+pub mod splits;
+pub mod statement;
 pub mod sub_record;
+pub mod tag_catalog;
 pub mod tag_record;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod text;
+pub mod time_series;
 pub mod txt_record;
+pub mod unit;
@@ -1,18 +1,21 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use super::date::{self, DateInterval};
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsNum {
     pub adsh: String,
     pub tag: String,
     pub version: String,
-    pub ddate: String,
+    #[serde(with = "date::yyyymmdd")]
+    pub ddate: NaiveDate,
     pub qtrs: Option<u16>,
     pub uom: String,
     pub dimh: String,
     pub iprx: Option<u16>,
-    pub value: Option<f32>,
+    pub value: Option<f64>,
     pub footnote: String,
     pub footlen: Option<u32>,
     pub dimn: Option<u8>,
@@ -22,6 +25,13 @@ pub struct FsNum {
     pub dcml: Option<f32>,
 }
 
+impl FsNum {
+    /// Start and end dates of the period this value covers
+    pub fn interval(&self) -> Option<DateInterval> {
+        DateInterval::from_ddate_qtrs(self.ddate, self.qtrs?)
+    }
+}
+
 impl FsRecord for FsNum {
     fn csv_filename() -> String {
         "num.tsv".to_string()
@@ -1,13 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+use super::codes::StatementType;
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsPre {
     pub adsh: String,
     pub report: Option<u16>,
     pub line: Option<u32>,
-    pub stmt: String,
+    pub stmt: StatementType,
     pub inpth: Option<u8>,
     pub tag: String,
     pub version: String,
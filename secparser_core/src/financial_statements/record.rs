@@ -1,15 +1,21 @@
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu, Whatever};
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::vec;
 
 use crate::data_source::{DataSource, DataSourceError};
 use crate::downloader::{DownloadConfig, DownloadConfigBuilder};
 use crate::financial_statements::data_source::FsDataSources;
-use crate::zip_csv_records::{CsvConfig, CsvConfigBuilder, ZipCsvRecords, ZipCsvRecordsError};
+use crate::financial_statements::filter::FsFilter;
+use crate::financial_statements::sub_record::FsSub;
+use crate::zip_csv_records::{
+    CsvConfig, CsvConfigBuilder, RecordFilter, ZipCsvRecords, ZipCsvRecordsError,
+};
 
 #[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
 pub enum FsRecordsError {
     #[snafu(display("Failed to process csv"))]
     ZipCsv { source: ZipCsvRecordsError },
@@ -20,10 +26,36 @@ pub enum FsRecordsError {
 
 pub trait FsRecord: Serialize + DeserializeOwned + Debug {
     fn csv_filename() -> String;
+
+    /// Whether rows belong to a submission, i.e. the file has an `adsh` column
+    fn has_adsh() -> bool {
+        true
+    }
 }
 
 pub type DataSourceIter = vec::IntoIter<DataSource>;
 
+#[derive(Deserialize)]
+struct SubAdsh {
+    adsh: String,
+}
+
+/// Gets the `adsh` of submissions in the data source matching the `sub.tsv` predicates
+pub fn get_sub_adshs(
+    data_source: &DataSource,
+    config: &CsvConfig,
+    filter: &FsFilter,
+) -> Result<HashSet<String>, ZipCsvRecordsError> {
+    let records: ZipCsvRecords<SubAdsh> = ZipCsvRecords::new_with_filter(
+        data_source,
+        config,
+        &FsSub::csv_filename(),
+        &filter.sub_filter(),
+    )?;
+
+    Ok(records.map(|r| r.adsh).collect())
+}
+
 pub struct FsRecords<T>
 where
     T: FsRecord,
@@ -32,6 +64,7 @@ where
     pub data_source_iter: DataSourceIter,
     pub maybe_records: Option<ZipCsvRecords<T>>,
     pub csv_filename: String,
+    pub filter: FsFilter,
 }
 
 impl<T> FsRecords<T>
@@ -42,6 +75,15 @@ where
         download_config: &DownloadConfig,
         csv_config: CsvConfig,
         from_year: i32,
+    ) -> Result<Self, FsRecordsError> {
+        Self::new_with_filter(download_config, csv_config, from_year, FsFilter::default())
+    }
+
+    pub fn new_with_filter(
+        download_config: &DownloadConfig,
+        csv_config: CsvConfig,
+        from_year: i32,
+        filter: FsFilter,
     ) -> Result<Self, FsRecordsError> {
         let data_sources =
             FsDataSources::new(download_config, from_year).context(DataSourceSnafu)?;
@@ -52,6 +94,7 @@ where
             data_source_iter,
             maybe_records: None,
             csv_filename: T::csv_filename(),
+            filter,
         };
 
         result.get_maybe_record_iter().context(ZipCsvSnafu)?;
@@ -67,8 +110,13 @@ where
                     data_source.filepath.display(),
                     self.csv_filename
                 );
-                let records: ZipCsvRecords<T> =
-                    ZipCsvRecords::new(&data_source, &self.config, &self.csv_filename)?;
+                let filter = self.get_record_filter(&data_source)?;
+                let records: ZipCsvRecords<T> = ZipCsvRecords::new_with_filter(
+                    &data_source,
+                    &self.config,
+                    &self.csv_filename,
+                    &filter,
+                )?;
 
                 self.maybe_records = Some(records);
 
@@ -80,6 +128,26 @@ where
             }
         }
     }
+
+    fn get_record_filter(
+        &self,
+        data_source: &DataSource,
+    ) -> Result<RecordFilter, ZipCsvRecordsError> {
+        if self.csv_filename == FsSub::csv_filename() {
+            return Ok(self.filter.sub_filter());
+        }
+
+        if !T::has_adsh() {
+            return Ok(self.filter.record_filter(None));
+        }
+
+        if !self.filter.has_sub_predicates() {
+            return Ok(self.filter.record_filter(self.filter.adshs.as_ref()));
+        }
+
+        let adshs = get_sub_adshs(data_source, &self.config, &self.filter)?;
+        Ok(self.filter.record_filter(Some(&adshs)))
+    }
 }
 
 impl<T> Iterator for FsRecords<T>
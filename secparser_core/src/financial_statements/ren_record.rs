@@ -1,13 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+use super::codes::MenuCategory;
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsRen {
     pub adsh: String,
     pub report: Option<u16>,
     pub rfile: String,
-    pub menucat: String,
+    pub menucat: Option<MenuCategory>,
     pub shortname: String,
     pub longname: String,
     pub roleuri: String,
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, NaiveDate};
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use snafu::{Location, ResultExt, Snafu};
+
+use super::date;
+use super::filing::FsFiling;
+use super::num_record::FsNum;
+use super::sub_record::FsSub;
+use super::unit::UnitKind;
+
+/// Tags reporting the number of new shares per old share of a split
+pub const SPLIT_RATIO_TAGS: [&str; 2] = [
+    "StockholdersEquityNoteStockSplitConversionRatio1",
+    "StockholdersEquityNoteStockSplitConversionRatio",
+];
+
+/// Text facts whose tag contains this are searched for the effective date of a split
+/// reported over a period, e.g. a custom `StockSplitEffectiveDate`
+const SPLIT_DATE_TAG_PART: &str = "StockSplit";
+
+/// Splits with the same ratio this many days apart or less are the same split. DERA rounds the
+/// `ddate` of an instant to the end of the month, so a detected date may differ from the exact
+/// one by a few weeks.
+const SAME_SPLIT_WINDOW_DAYS: i64 = 45;
+
+#[derive(Debug, Snafu)]
+pub enum SplitError {
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to read splits from {filepath:?}"))]
+    Csv {
+        source: csv::Error,
+        filepath: PathBuf,
+    },
+}
+
+/// A stock split, e.g. a ratio of 4 for a 4-for-1 split or 0.1 for a 1-for-10 reverse split
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StockSplit {
+    pub cik: usize,
+    #[serde(with = "date::iso_date")]
+    pub date: NaiveDate,
+    pub ratio: f64,
+}
+
+/// Stock splits per CIK, detected from filings or loaded from a CSV file
+#[derive(Debug, Default)]
+pub struct SplitTable {
+    splits: HashMap<usize, Vec<StockSplit>>,
+}
+
+impl SplitTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads splits from a CSV file with a `cik,date,ratio` header, where `date` is `YYYY-MM-DD`
+    pub fn from_path(filepath: &Path) -> Result<Self, SplitError> {
+        let mut reader = ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(filepath)
+            .context(CsvSnafu { filepath })?;
+
+        let mut table = Self::new();
+        for record in reader.deserialize() {
+            table.insert(record.context(CsvSnafu { filepath })?);
+        }
+
+        Ok(table)
+    }
+
+    /// Adds a split, ignoring it if a split with the same ratio is already known within
+    /// `SAME_SPLIT_WINDOW_DAYS`, e.g. when disclosed again in later filings. A known split dated
+    /// at a month end, possibly rounded, is replaced by one with an exact date.
+    pub fn insert(&mut self, split: StockSplit) {
+        if split.ratio <= 0.0 || split.ratio == 1.0 {
+            return;
+        }
+
+        let splits = self.splits.entry(split.cik).or_default();
+        let known = splits.iter().position(|s| {
+            s.ratio == split.ratio
+                && (s.date - split.date).num_days().abs() <= SAME_SPLIT_WINDOW_DAYS
+        });
+        match known {
+            Some(i) if is_month_end(splits[i].date) && !is_month_end(split.date) => {
+                splits.remove(i);
+            }
+            Some(_) => return,
+            None => {}
+        }
+
+        let i = splits.partition_point(|s| s.date <= split.date);
+        splits.insert(i, split);
+    }
+
+    /// Adds the splits reported with `SPLIT_RATIO_TAGS` in a filing, dated at their effective
+    /// date.
+    ///
+    /// A ratio reported at an instant (`qtrs` 0) is dated at that instant. A ratio reported over
+    /// a period is dated by a text fact of the filing with a `StockSplit` tag and a
+    /// `YYYY-MM-DD` value between the start of the period and the filing date, and skipped if
+    /// there is none, since the end of the period is usually not the date of the split.
+    pub fn detect(&mut self, filing: &FsFiling) {
+        let splits = filing
+            .nums
+            .iter()
+            .filter(|n| SPLIT_RATIO_TAGS.contains(&n.tag.as_str()))
+            .filter_map(|n| {
+                let interval = n.interval()?;
+                let date = match interval.is_instant() {
+                    true => Some(interval.end),
+                    false => effective_date(filing, interval.start),
+                };
+                let split = StockSplit {
+                    cik: filing.sub.cik,
+                    date: date?,
+                    ratio: n.value?,
+                };
+                Some(split)
+            })
+            .collect::<Vec<_>>();
+
+        for split in splits {
+            self.insert(split);
+        }
+    }
+
+    pub fn splits(&self, cik: usize) -> &[StockSplit] {
+        self.splits.get(&cik).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Cumulative ratio of the splits after `reported_on`.
+    ///
+    /// Filings restate prior periods for splits that happen before they are issued, so only
+    /// later splits are missing from values reported on that date.
+    pub fn factor(&self, cik: usize, reported_on: NaiveDate) -> f64 {
+        self.splits(cik)
+            .iter()
+            .filter(|s| s.date > reported_on)
+            .map(|s| s.ratio)
+            .product()
+    }
+
+    /// Restates a share count or per-share value to the current share basis. Other values are
+    /// returned unchanged.
+    pub fn adjust(&self, sub: &FsSub, num: &FsNum) -> FsNum {
+        let factor = self.factor(sub.cik, sub.filed);
+        let value = match num.unit().kind() {
+            UnitKind::Shares => num.value.map(|v| v * factor),
+            UnitKind::PerShare => num.value.map(|v| v / factor),
+            _ => num.value,
+        };
+
+        FsNum {
+            value,
+            ..num.clone()
+        }
+    }
+
+    /// Values of a filing restated to the current share basis
+    pub fn adjust_filing(&self, filing: &FsFiling) -> Vec<FsNum> {
+        filing
+            .nums
+            .iter()
+            .map(|num| self.adjust(&filing.sub, num))
+            .collect()
+    }
+}
+
+fn is_month_end(date: NaiveDate) -> bool {
+    date.succ_opt().is_some_and(|next| next.day() == 1)
+}
+
+/// Date value of a split text fact between `start` and the filing date. Splits after the end
+/// of the period are often disclosed as subsequent events.
+fn effective_date(filing: &FsFiling, start: NaiveDate) -> Option<NaiveDate> {
+    filing
+        .txts
+        .iter()
+        .filter(|t| t.tag.contains(SPLIT_DATE_TAG_PART))
+        .filter_map(|t| NaiveDate::parse_from_str(t.value.trim(), "%Y-%m-%d").ok())
+        .find(|date| (start..=filing.sub.filed).contains(date))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{
+        fs_filing, num_line, parse_tsv, txt_line, SubLine, TXT_HEADER,
+    };
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn it_adjusts_for_splits() -> Result<(), SplitError> {
+        let filepath = std::env::temp_dir().join("secparser_splits.csv");
+        std::fs::write(
+            &filepath,
+            "cik,date,ratio\n1,2022-06-30,2\n1,2020-01-01,1\n",
+        )?;
+        let mut table = SplitTable::from_path(&filepath)?;
+
+        let mut filing = fs_filing(
+            SubLine {
+                period: "20241231",
+                filed: "20250214",
+                ..SubLine::new("k2", 1, "10-K")
+            },
+            &[
+                num_line("k2", SPLIT_RATIO_TAGS[0], "20240630", 0, "pure", 4.0),
+                num_line("k2", SPLIT_RATIO_TAGS[1], "20241231", 4, "pure", 2.0),
+            ],
+            &[],
+            &[],
+        );
+        table.detect(&filing);
+        assert_eq!(table.splits(1).len(), 2);
+        assert_eq!(table.splits(1)[1].date, date(2024, 6, 30));
+
+        table.insert(StockSplit {
+            cik: 1,
+            date: date(2024, 6, 10),
+            ratio: 4.0,
+        });
+        table.detect(&filing);
+        assert_eq!(table.splits(1).len(), 2);
+        assert_eq!(table.splits(1)[1].date, date(2024, 6, 10));
+
+        filing.txts = parse_tsv(
+            TXT_HEADER,
+            &[txt_line(
+                "k2",
+                "StockSplitEffectiveDate",
+                "20241231",
+                4,
+                "2025-01-15",
+            )],
+        );
+        table.detect(&filing);
+        assert_eq!(table.splits(1).len(), 3);
+        assert_eq!(table.splits(1)[2].date, date(2025, 1, 15));
+        assert_eq!(table.splits(1)[2].ratio, 2.0);
+        assert_eq!(table.factor(1, date(2022, 1, 1)), 16.0);
+        assert_eq!(table.factor(1, date(2024, 12, 31)), 2.0);
+
+        let filing = fs_filing(
+            SubLine::new("k", 1, "10-K"),
+            &[
+                num_line(
+                    "k",
+                    "EarningsPerShareBasic",
+                    "20231231",
+                    4,
+                    "USD/shares",
+                    8.0,
+                ),
+                num_line(
+                    "k",
+                    "CommonStockSharesOutstanding",
+                    "20231231",
+                    0,
+                    "shares",
+                    100.0,
+                ),
+                num_line("k", "Revenues", "20231231", 4, "USD", 50.0),
+            ],
+            &[],
+            &[],
+        );
+        let adjusted = table
+            .adjust_filing(&filing)
+            .into_iter()
+            .filter_map(|n| n.value)
+            .collect::<Vec<_>>();
+
+        assert_eq!(adjusted, vec![1.0, 800.0, 50.0]);
+
+        Ok(())
+    }
+}
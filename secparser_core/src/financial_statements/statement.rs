@@ -0,0 +1,381 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+use csv::Writer;
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use crate::downloader::DownloadConfig;
+use crate::zip_csv_records::CsvConfig;
+
+use super::codes::StatementType;
+use super::filing::{FsFiling, FsFilings};
+use super::filter::FsFilterBuilder;
+use super::num_record::FsNum;
+use super::pre_record::FsPre;
+use super::record::FsRecordsError;
+
+/// `dimh` of values without any dimension
+pub const NO_DIMENSIONS_HASH: &str = "0x00000000";
+
+#[derive(Debug, Snafu)]
+pub enum StatementError {
+    #[snafu(display("Failed to get filings"))]
+    Filings { source: FsRecordsError },
+
+    #[snafu(display("Could not find filing {adsh}"))]
+    FilingNotFound { adsh: String },
+
+    #[snafu(display("Could not find statement {stmt} in filing {adsh}"))]
+    StatementNotFound { adsh: String, stmt: StatementType },
+
+    #[snafu(display("Failed to write csv"))]
+    Csv { source: csv::Error },
+}
+
+/// A column of a rendered statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StatementPeriod {
+    pub ddate: NaiveDate,
+    pub qtrs: u16,
+}
+
+impl StatementPeriod {
+    pub fn label(&self) -> String {
+        match self.qtrs {
+            0 => self.ddate.format("%Y-%m-%d").to_string(),
+            qtrs => format!("{}M {}", qtrs * 3, self.ddate.format("%Y-%m-%d")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatementLine {
+    pub line: u32,
+    pub tag: String,
+    pub version: String,
+    pub label: String,
+    pub indent: u8,
+    pub negating: bool,
+    /// Values as rendered, i.e. with the sign flipped for negating labels, one per period
+    pub values: Vec<Option<f64>>,
+}
+
+/// A financial statement as rendered in the filing
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub adsh: String,
+    pub report: u16,
+    pub stmt: StatementType,
+    pub name: String,
+    pub periods: Vec<StatementPeriod>,
+    pub lines: Vec<StatementLine>,
+}
+
+impl Statement {
+    /// Builds the first report of the given statement type in the filing
+    pub fn build(filing: &FsFiling, stmt: &StatementType) -> Option<Self> {
+        let report = filing
+            .pres
+            .iter()
+            .filter(|p| &p.stmt == stmt)
+            .filter_map(|p| p.report)
+            .min()?;
+
+        Some(Self::build_report(filing, report))
+    }
+
+    /// Builds every balance sheet, income statement, cash flow, equity and comprehensive
+    /// income report in the filing
+    pub fn build_all(filing: &FsFiling) -> Vec<Self> {
+        let reports = filing
+            .pres
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p.stmt,
+                    StatementType::BalanceSheet
+                        | StatementType::IncomeStatement
+                        | StatementType::CashFlow
+                        | StatementType::Equity
+                        | StatementType::ComprehensiveIncome
+                )
+            })
+            .filter_map(|p| p.report)
+            .collect::<BTreeSet<_>>();
+
+        reports
+            .into_iter()
+            .map(|report| Self::build_report(filing, report))
+            .collect()
+    }
+
+    fn build_report(filing: &FsFiling, report: u16) -> Self {
+        let mut pres = filing
+            .pres
+            .iter()
+            .filter(|p| p.report == Some(report))
+            .collect::<Vec<_>>();
+        pres.sort_by_key(|p| p.line);
+
+        let stmt = pres
+            .first()
+            .map(|p| p.stmt.clone())
+            .unwrap_or(StatementType::Unclassifiable);
+        let name = filing
+            .rens
+            .iter()
+            .find(|r| r.report == Some(report))
+            .map(|r| r.shortname.to_string())
+            .unwrap_or_default();
+
+        let nums_by_line = pres
+            .iter()
+            .map(|pre| {
+                filing
+                    .nums
+                    .iter()
+                    .filter(|num| Self::is_line_value(pre, num))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let periods = nums_by_line
+            .iter()
+            .flatten()
+            .filter_map(|num| {
+                num.qtrs.map(|qtrs| StatementPeriod {
+                    ddate: num.ddate,
+                    qtrs,
+                })
+            })
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>();
+
+        let lines = pres
+            .iter()
+            .zip(nums_by_line.iter())
+            .map(|(pre, nums)| {
+                let negating = pre.negating == Some(1);
+                let values = periods
+                    .iter()
+                    .map(|period| {
+                        nums.iter()
+                            .find(|num| num.ddate == period.ddate && num.qtrs == Some(period.qtrs))
+                            .and_then(|num| num.value)
+                            .map(|v| if negating { -v } else { v })
+                    })
+                    .collect();
+
+                StatementLine {
+                    line: pre.line.unwrap_or_default(),
+                    tag: pre.tag.to_string(),
+                    version: pre.version.to_string(),
+                    label: pre.plabel.to_string(),
+                    indent: pre.inpth.unwrap_or_default(),
+                    negating,
+                    values,
+                }
+            })
+            .collect();
+
+        Self {
+            adsh: filing.sub.adsh.to_string(),
+            report,
+            stmt,
+            name,
+            periods,
+            lines,
+        }
+    }
+
+    fn is_line_value(pre: &FsPre, num: &FsNum) -> bool {
+        num.tag == pre.tag
+            && num.version == pre.version
+            && num.dimh == NO_DIMENSIONS_HASH
+            && num.coreg.is_empty()
+    }
+
+    pub fn to_csv(&self) -> Result<String, StatementError> {
+        let mut writer = Writer::from_writer(vec![]);
+
+        let mut header = vec![
+            "line".to_string(),
+            "label".to_string(),
+            "indent".to_string(),
+        ];
+        header.extend(self.periods.iter().map(|p| p.label()));
+        writer.write_record(&header).context(CsvSnafu)?;
+
+        for line in &self.lines {
+            let mut record = vec![
+                line.line.to_string(),
+                line.label.to_string(),
+                line.indent.to_string(),
+            ];
+            record.extend(
+                line.values
+                    .iter()
+                    .map(|v| v.map(|v| v.to_string()).unwrap_or_default()),
+            );
+            writer.write_record(&record).context(CsvSnafu)?;
+        }
+
+        let data = writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))
+            .context(CsvSnafu)?;
+
+        Ok(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut result = String::new();
+
+        let _ = writeln!(result, "## {}", self.name);
+        let _ = writeln!(result);
+
+        let header = self
+            .periods
+            .iter()
+            .map(|p| p.label())
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let _ = writeln!(result, "| Line item | {header} |");
+        let _ = writeln!(result, "|---|{}", "---:|".repeat(self.periods.len()));
+
+        for line in &self.lines {
+            let indent = "&nbsp;&nbsp;".repeat(line.indent as usize);
+            let label = line.label.replace('|', "\\|");
+            let values = line
+                .values
+                .iter()
+                .map(|v| v.map(|v| v.to_string()).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let _ = writeln!(result, "| {indent}{label} | {values} |");
+        }
+
+        result
+    }
+}
+
+/// Builds statements of a filing looked up by `adsh`
+pub struct StatementBuilder {
+    download_config: DownloadConfig,
+    csv_config: CsvConfig,
+    from_year: i32,
+}
+
+impl StatementBuilder {
+    pub fn new(download_config: &DownloadConfig, csv_config: CsvConfig, from_year: i32) -> Self {
+        Self {
+            download_config: download_config.clone(),
+            csv_config,
+            from_year,
+        }
+    }
+
+    pub fn get_filing(&self, adsh: &str) -> Result<FsFiling, StatementError> {
+        let filter = FsFilterBuilder::default()
+            .adshs([adsh])
+            .build()
+            .unwrap_or_default();
+
+        FsFilings::new(
+            &self.download_config,
+            self.csv_config.clone(),
+            self.from_year,
+            filter,
+        )
+        .context(FilingsSnafu)?
+        .next()
+        .context(FilingNotFoundSnafu { adsh })
+    }
+
+    pub fn build(&self, adsh: &str, stmt: &StatementType) -> Result<Statement, StatementError> {
+        let filing = self.get_filing(adsh)?;
+
+        Statement::build(&filing, stmt).context(StatementNotFoundSnafu {
+            adsh,
+            stmt: stmt.clone(),
+        })
+    }
+
+    pub fn build_all(&self, adsh: &str) -> Result<Vec<Statement>, StatementError> {
+        let filing = self.get_filing(adsh)?;
+
+        Ok(Statement::build_all(&filing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::SubLine;
+    use crate::financial_statements::test_utils::{fs_filing, num_line, pre_line, ren_line};
+
+    use super::*;
+
+    fn filing() -> FsFiling {
+        fs_filing(
+            SubLine::new("a", 1, "10-K"),
+            &[
+                num_line("a", "Revenues", "20231231", 4, "USD", 100.0),
+                num_line("a", "Revenues", "20221231", 4, "USD", 90.0),
+                num_line("a", "CostOfRevenue", "20231231", 4, "USD", 60.0),
+                num_line("a", "CostOfRevenue", "20221231", 4, "USD", 50.0),
+                num_line("a", "GrossProfit", "20231231", 4, "USD", 40.0),
+                num_line("a", "Assets", "20231231", 0, "USD", 500.0),
+            ],
+            &[
+                pre_line("a", 4, 2, "IS", 1, "CostOfRevenue", "Cost of revenue", 1),
+                pre_line("a", 4, 1, "IS", 1, "Revenues", "Revenue", 0),
+                pre_line("a", 4, 3, "IS", 0, "GrossProfit", "Gross profit", 0),
+                pre_line("a", 2, 1, "BS", 0, "Assets", "Total assets", 0),
+            ],
+            &[
+                ren_line("a", 2, "S", "Balance Sheets"),
+                ren_line("a", 4, "S", "Statements of Operations"),
+            ],
+        )
+    }
+
+    #[test]
+    fn it_builds_statement() {
+        let filing = filing();
+        let statement = Statement::build(&filing, &StatementType::IncomeStatement).unwrap();
+
+        assert_eq!(statement.report, 4);
+        assert_eq!(statement.name, "Statements of Operations");
+        assert_eq!(statement.periods.len(), 2);
+        assert_eq!(statement.periods[0].label(), "12M 2023-12-31");
+
+        let labels = statement.lines.iter().map(|l| l.label.as_str());
+        assert_eq!(
+            labels.collect::<Vec<_>>(),
+            vec!["Revenue", "Cost of revenue", "Gross profit"]
+        );
+        assert_eq!(statement.lines[1].values, vec![Some(-60.0), Some(-50.0)]);
+        assert_eq!(statement.lines[2].values, vec![Some(40.0), None]);
+
+        assert_eq!(Statement::build_all(&filing).len(), 2);
+    }
+
+    #[test]
+    fn it_exports_statement() {
+        let filing = filing();
+        let statement = Statement::build(&filing, &StatementType::IncomeStatement).unwrap();
+
+        let csv = statement.to_csv().unwrap();
+        assert_eq!(
+            csv.lines().next(),
+            Some("line,label,indent,12M 2023-12-31,12M 2022-12-31")
+        );
+        assert_eq!(csv.lines().nth(3), Some("3,Gross profit,0,40,"));
+
+        let markdown = statement.to_markdown();
+        assert!(markdown.contains("| &nbsp;&nbsp;Cost of revenue | -60 | -50 |"));
+    }
+}
@@ -1,8 +1,11 @@
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
+use super::codes::{FilerStatus, FiscalPeriod, FormType};
+use super::date::{self, FiscalYearEnd};
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsSub {
     pub adsh: String,
     pub cik: usize,
@@ -29,16 +32,21 @@ pub struct FsSub {
 
     pub ein: String,
     pub former: String,
-    pub changed: String,
-    pub afs: String,
+    #[serde(with = "date::yyyymmdd_opt")]
+    pub changed: Option<NaiveDate>,
+    pub afs: Option<FilerStatus>,
     pub wksi: Option<u8>,
-    pub fye: String,
-    pub form: String,
-    pub period: String,
-    pub fy: String,
-    pub fp: String,
-    pub filed: String,
-    pub accepted: String,
+    #[serde(with = "date::fiscal_year_end_opt")]
+    pub fye: Option<FiscalYearEnd>,
+    pub form: FormType,
+    #[serde(with = "date::yyyymmdd")]
+    pub period: NaiveDate,
+    pub fy: Option<i32>,
+    pub fp: Option<FiscalPeriod>,
+    #[serde(with = "date::yyyymmdd")]
+    pub filed: NaiveDate,
+    #[serde(with = "date::datetime")]
+    pub accepted: NaiveDateTime,
 
     pub prevrpt: Option<u8>,
     pub detail: Option<u8>,
@@ -46,7 +54,8 @@ pub struct FsSub {
     pub nciks: Option<u16>,
     pub aciks: String,
     pub pubfloatusd: Option<f32>,
-    pub floatdate: String,
+    #[serde(with = "date::yyyymmdd_opt")]
+    pub floatdate: Option<NaiveDate>,
     pub floataxis: String,
     pub floatmems: Option<u8>,
 }
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use snafu::{Location, ResultExt, Snafu};
+
+use crate::downloader::DownloadConfig;
+use crate::zip_csv_records::CsvConfig;
+
+use super::record::{FsRecords, FsRecordsError};
+use super::tag_record::FsTag;
+
+#[derive(Debug, Snafu)]
+pub enum TagCatalogError {
+    #[snafu(display("Failed to get tag records"))]
+    TagRecords { source: FsRecordsError },
+
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to (de)serialize {filepath:?}"))]
+    Json {
+        source: serde_json::Error,
+        filepath: PathBuf,
+    },
+}
+
+impl FsTag {
+    /// Whether the tag is defined by the filer rather than a standard taxonomy
+    pub fn is_custom(&self) -> bool {
+        self.custom == Some(1)
+    }
+
+    /// Taxonomy and year of a standard version, e.g. `("us-gaap", 2023)` for `us-gaap/2023`
+    pub fn taxonomy_year(&self) -> Option<(&str, u16)> {
+        let (taxonomy, year) = self.version.split_once('/')?;
+        year.parse().ok().map(|year| (taxonomy, year))
+    }
+}
+
+/// Tag definitions keyed by (tag, version), built from all `tag.tsv` files
+#[derive(Debug, Default, Deserialize)]
+#[serde(from = "Vec<FsTag>")]
+pub struct TagCatalog {
+    tags: Vec<FsTag>,
+    index: HashMap<(String, String), usize>,
+    by_tag: HashMap<String, Vec<usize>>,
+}
+
+impl TagCatalog {
+    pub fn new(
+        download_config: &DownloadConfig,
+        csv_config: CsvConfig,
+        from_year: i32,
+    ) -> Result<Self, TagCatalogError> {
+        let records: FsRecords<FsTag> =
+            FsRecords::new(download_config, csv_config, from_year).context(TagRecordsSnafu)?;
+
+        Ok(records.collect())
+    }
+
+    pub fn load(filepath: &Path) -> Result<Self, TagCatalogError> {
+        let reader = BufReader::new(File::open(filepath)?);
+
+        serde_json::from_reader(reader).context(JsonSnafu { filepath })
+    }
+
+    pub fn save(&self, filepath: &Path) -> Result<(), TagCatalogError> {
+        let writer = BufWriter::new(File::create(filepath)?);
+
+        serde_json::to_writer(writer, &self.tags).context(JsonSnafu { filepath })
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    pub fn insert(&mut self, tag: FsTag) {
+        let key = (tag.tag.to_string(), tag.version.to_string());
+        if self.index.contains_key(&key) {
+            return;
+        }
+
+        let i = self.tags.len();
+        self.by_tag.entry(tag.tag.to_string()).or_default().push(i);
+        self.index.insert(key, i);
+        self.tags.push(tag);
+    }
+
+    pub fn get(&self, tag: &str, version: &str) -> Option<&FsTag> {
+        self.index
+            .get(&(tag.to_string(), version.to_string()))
+            .map(|i| &self.tags[*i])
+    }
+
+    /// All versions of a tag
+    pub fn versions(&self, tag: &str) -> impl Iterator<Item = &FsTag> {
+        self.by_tag
+            .get(tag)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.tags[*i])
+    }
+
+    /// The standard definition of a tag from the most recent taxonomy year
+    pub fn latest_standard(&self, tag: &str) -> Option<&FsTag> {
+        self.versions(tag)
+            .filter(|t| !t.is_custom())
+            .max_by_key(|t| t.taxonomy_year().map(|(_, year)| year))
+    }
+
+    pub fn standard(&self) -> impl Iterator<Item = &FsTag> {
+        self.tags.iter().filter(|t| !t.is_custom())
+    }
+
+    pub fn custom(&self) -> impl Iterator<Item = &FsTag> {
+        self.tags.iter().filter(|t| t.is_custom())
+    }
+
+    /// Tags whose label contains the query, ignoring case
+    pub fn search_label(&self, query: &str) -> Vec<&FsTag> {
+        let query = query.to_lowercase();
+
+        self.tags
+            .iter()
+            .filter(|t| t.tlabel.to_lowercase().contains(&query))
+            .collect()
+    }
+}
+
+impl FromIterator<FsTag> for TagCatalog {
+    fn from_iter<I: IntoIterator<Item = FsTag>>(iter: I) -> Self {
+        let mut catalog = Self::default();
+        for tag in iter {
+            catalog.insert(tag);
+        }
+        catalog
+    }
+}
+
+impl From<Vec<FsTag>> for TagCatalog {
+    fn from(tags: Vec<FsTag>) -> Self {
+        tags.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::codes::CreditDebit;
+    use crate::financial_statements::test_utils::{parse_tsv, TAG_HEADER};
+
+    use super::*;
+
+    fn catalog() -> TagCatalog {
+        parse_tsv::<FsTag>(
+            TAG_HEADER,
+            &[
+                "Revenues\tus-gaap/2022\t0\t0\tmonetary\tD\tC\tRevenues\tOld".to_string(),
+                "Revenues\tus-gaap/2023\t0\t0\tmonetary\tD\tC\tRevenues\tNew".to_string(),
+                "Revenues\tus-gaap/2023\t0\t0\tmonetary\tD\tC\tRevenues\tDuplicate".to_string(),
+                "CustomRevenue\t0000320193-23-000106\t1\t0\tmonetary\tD\tC\tCustom Revenue\t"
+                    .to_string(),
+                "Assets\tus-gaap/2023\t0\t0\tmonetary\tI\tD\tAssets\t".to_string(),
+            ],
+        )
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn it_looks_up_tags() {
+        let catalog = catalog();
+
+        assert_eq!(catalog.len(), 4);
+        assert_eq!(catalog.get("Revenues", "us-gaap/2023").unwrap().doc, "New");
+        assert_eq!(catalog.latest_standard("Revenues").unwrap().doc, "New");
+        assert_eq!(
+            catalog.get("Assets", "us-gaap/2023").unwrap().crdr,
+            Some(CreditDebit::Debit)
+        );
+        assert_eq!(catalog.custom().count(), 1);
+        assert_eq!(catalog.standard().count(), 3);
+        assert_eq!(catalog.search_label("revenue").len(), 3);
+    }
+
+    #[test]
+    fn it_saves_and_loads() -> Result<(), TagCatalogError> {
+        let filepath = std::env::temp_dir().join("secparser_tag_catalog.json");
+        catalog().save(&filepath)?;
+
+        let loaded = TagCatalog::load(&filepath)?;
+        assert_eq!(loaded.len(), 4);
+        assert!(loaded
+            .get("CustomRevenue", "0000320193-23-000106")
+            .is_some());
+
+        Ok(())
+    }
+}
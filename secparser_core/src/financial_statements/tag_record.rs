@@ -1,16 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use super::codes::{CreditDebit, DataType, InstantOrDuration};
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsTag {
     pub tag: String,
     pub version: String,
     pub custom: Option<u8>,
     pub r#abstract: Option<u8>,
-    pub datatype: String,
-    pub iord: String,
-    pub crdr: String,
+    pub datatype: Option<DataType>,
+    pub iord: Option<InstantOrDuration>,
+    pub crdr: Option<CreditDebit>,
     pub tlabel: String,
     pub doc: String,
 }
@@ -19,6 +20,10 @@ impl FsRecord for FsTag {
     fn csv_filename() -> String {
         "tag.tsv".to_string()
     }
+
+    fn has_adsh() -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,143 @@
+use csv::ReaderBuilder;
+use serde::de::DeserializeOwned;
+
+use crate::data_source::DataSource;
+use crate::zip_csv_records::write_test_zip;
+
+use super::filing::FsFiling;
+
+pub const SUB_HEADER: &str = "adsh\tcik\tname\tsic\tcountryba\tstprba\tcityba\tzipba\tbas1\tbas2\tbaph\tcountryma\tstprma\tcityma\tzipma\tmas1\tmas2\tcountryinc\tstprinc\tein\tformer\tchanged\tafs\twksi\tfye\tform\tperiod\tfy\tfp\tfiled\taccepted\tprevrpt\tdetail\tinstance\tnciks\taciks\tpubfloatusd\tfloatdate\tfloataxis\tfloatmems";
+pub const NUM_HEADER: &str = "adsh\ttag\tversion\tddate\tqtrs\tuom\tdimh\tiprx\tvalue\tfootnote\tfootlen\tdimn\tcoreg\tdurp\tdatp\tdcml";
+pub const PRE_HEADER: &str =
+    "adsh\treport\tline\tstmt\tinpth\ttag\tversion\tprole\tplabel\tnegating";
+pub const REN_HEADER: &str = "adsh\treport\trfile\tmenucat\tshortname\tlongname\troleuri\tparentroleuri\tparentreport\tultparentrpt";
+pub const CAL_HEADER: &str = "adsh\tgrp\tarc\tnegative\tptag\tpversion\tctag\tcversion";
+pub const TAG_HEADER: &str = "tag\tversion\tcustom\tabstract\tdatatype\tiord\tcrdr\ttlabel\tdoc";
+pub const TXT_HEADER: &str = "adsh\ttag\tversion\tddate\tqtrs\tiprx\tlang\tdcml\tdurp\tdatp\tdimh\tdimn\tcoreg\tescaped\tsrclen\ttxtlen\tfootnote\tfootlen\tcontext\tvalue";
+
+pub struct SubLine<'a> {
+    pub adsh: &'a str,
+    pub cik: usize,
+    pub form: &'a str,
+    pub period: &'a str,
+    pub fy: &'a str,
+    pub fp: &'a str,
+    pub filed: &'a str,
+    pub accepted: &'a str,
+    pub prevrpt: u8,
+}
+
+impl<'a> SubLine<'a> {
+    pub fn new(adsh: &'a str, cik: usize, form: &'a str) -> Self {
+        Self {
+            adsh,
+            cik,
+            form,
+            period: "20231231",
+            fy: "2023",
+            fp: "FY",
+            filed: "20240215",
+            accepted: "2024-02-15 16:05:00.0",
+            prevrpt: 0,
+        }
+    }
+}
+
+pub fn sub_line(sub: SubLine) -> String {
+    format!(
+        "{}\t{}\tTEST CORP\t3571\tUS\tCA\tCUPERTINO\t95014\tONE WAY\t\t408\tUS\tCA\tCUPERTINO\t95014\tONE WAY\t\tUS\tCA\t123\t\t\t1-LAF\t0\t1231\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t1\ttest.htm\t1\t\t\t\t\t",
+        sub.adsh, sub.cik, sub.form, sub.period, sub.fy, sub.fp, sub.filed, sub.accepted, sub.prevrpt
+    )
+}
+
+pub fn num_line(adsh: &str, tag: &str, ddate: &str, qtrs: u16, uom: &str, value: f64) -> String {
+    num_line_with_dim(adsh, tag, ddate, qtrs, uom, value, "0x00000000", 0)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn num_line_with_dim(
+    adsh: &str,
+    tag: &str,
+    ddate: &str,
+    qtrs: u16,
+    uom: &str,
+    value: f64,
+    dimh: &str,
+    dimn: u8,
+) -> String {
+    format!("{adsh}\t{tag}\tus-gaap/2023\t{ddate}\t{qtrs}\t{uom}\t{dimh}\t0\t{value}\t\t\t{dimn}\t\t\t\t-6")
+}
+
+pub fn txt_line(adsh: &str, tag: &str, ddate: &str, qtrs: u16, value: &str) -> String {
+    format!("{adsh}\t{tag}\tus-gaap/2023\t{ddate}\t{qtrs}\t0\ten-US\t\t\t\t0x00000000\t0\t\t0\t\t\t\t\tc-1\t{value}")
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn pre_line(
+    adsh: &str,
+    report: u16,
+    line: u32,
+    stmt: &str,
+    inpth: u8,
+    tag: &str,
+    plabel: &str,
+    negating: u8,
+) -> String {
+    format!("{adsh}\t{report}\t{line}\t{stmt}\t{inpth}\t{tag}\tus-gaap/2023\tterseLabel\t{plabel}\t{negating}")
+}
+
+pub fn ren_line(adsh: &str, report: u16, menucat: &str, shortname: &str) -> String {
+    format!(
+        "{adsh}\t{report}\tH\t{menucat}\t{shortname}\t{shortname}\thttp://test/role/{report}\t\t\t"
+    )
+}
+
+pub fn cal_line(adsh: &str, grp: u8, arc: u8, negative: u8, ptag: &str, ctag: &str) -> String {
+    format!("{adsh}\t{grp}\t{arc}\t{negative}\t{ptag}\tus-gaap/2023\t{ctag}\tus-gaap/2023")
+}
+
+fn tsv(header: &str, lines: &[String]) -> String {
+    let mut result = header.to_string();
+    for line in lines {
+        result.push('\n');
+        result.push_str(line);
+    }
+    result.push('\n');
+    result
+}
+
+pub fn parse_tsv<T: DeserializeOwned>(header: &str, lines: &[String]) -> Vec<T> {
+    let content = tsv(header, lines);
+    ReaderBuilder::new()
+        .delimiter(b'\t')
+        .quoting(false)
+        .from_reader(content.as_bytes())
+        .into_deserialize()
+        .collect::<Result<Vec<T>, csv::Error>>()
+        .unwrap_or_else(|e| panic!("Should parse test tsv: {e}"))
+}
+
+pub fn fs_archive(name: &str, subs: &[String], nums: &[String], pres: &[String]) -> DataSource {
+    write_test_zip(
+        name,
+        &[
+            ("sub.tsv", &tsv(SUB_HEADER, subs)),
+            ("num.tsv", &tsv(NUM_HEADER, nums)),
+            ("pre.tsv", &tsv(PRE_HEADER, pres)),
+            ("ren.tsv", &tsv(REN_HEADER, &[])),
+            ("txt.tsv", &tsv(TXT_HEADER, &[])),
+            ("cal.tsv", &tsv(CAL_HEADER, &[])),
+        ],
+    )
+}
+
+pub fn fs_filing(sub: SubLine, nums: &[String], pres: &[String], rens: &[String]) -> FsFiling {
+    FsFiling {
+        sub: parse_tsv(SUB_HEADER, &[sub_line(sub)]).remove(0),
+        nums: parse_tsv(NUM_HEADER, nums),
+        txts: vec![],
+        pres: parse_tsv(PRE_HEADER, pres),
+        rens: parse_tsv(REN_HEADER, rens),
+        cals: vec![],
+    }
+}
@@ -0,0 +1,388 @@
+use super::txt_record::FsTxt;
+
+const NAMED_ENTITIES: [(&str, &str); 16] = [
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", " "),
+    ("ndash", "–"),
+    ("mdash", "—"),
+    ("lsquo", "‘"),
+    ("rsquo", "’"),
+    ("ldquo", "“"),
+    ("rdquo", "”"),
+    ("bull", "•"),
+    ("hellip", "…"),
+    ("reg", "®"),
+    ("copy", "©"),
+];
+
+const BLOCK_TAGS: [&str; 12] = [
+    "p",
+    "div",
+    "table",
+    "ul",
+    "ol",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+];
+
+const SKIPPED_TAGS: [&str; 3] = ["script", "style", "head"];
+
+impl FsTxt {
+    /// The value with DERA escaping reversed, so embedded HTML is restored
+    pub fn decoded_value(&self) -> String {
+        match self.escaped {
+            Some(1) => unescape(&self.value),
+            _ => self.value.to_string(),
+        }
+    }
+
+    /// Whether the value was truncated from its original length in the filing
+    pub fn is_truncated(&self) -> bool {
+        match (self.srclen, self.txtlen) {
+            (Some(srclen), Some(txtlen)) => txtlen < srclen,
+            _ => false,
+        }
+    }
+
+    pub fn plain_text(&self) -> String {
+        html_to_text(&self.decoded_value(), false)
+    }
+
+    pub fn markdown(&self) -> String {
+        html_to_text(&self.decoded_value(), true)
+    }
+}
+
+/// Reverses backslash escaping of whitespace and HTML entity escaping
+pub fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            _ => {
+                result.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    decode_entities(&result)
+}
+
+/// Decodes named and numeric HTML character references
+pub fn decode_entities(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end]).map(|v| (v, end)));
+
+        match decoded {
+            Some((v, end)) => {
+                result.push_str(&v);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+fn decode_entity(entity: &str) -> Option<String> {
+    let code = match entity.strip_prefix('#') {
+        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok(),
+        Some(dec) => dec.parse().ok(),
+        None => {
+            return NAMED_ENTITIES
+                .iter()
+                .find(|(name, _)| *name == entity)
+                .map(|(_, v)| v.to_string())
+        }
+    };
+
+    code.and_then(char::from_u32).map(|c| match c {
+        '\u{a0}' => " ".to_string(),
+        c => c.to_string(),
+    })
+}
+
+/// Converts HTML to plain text, or to Markdown when `markdown` is true
+pub fn html_to_text(html: &str, markdown: bool) -> String {
+    let mut renderer = Renderer {
+        markdown,
+        out: String::with_capacity(html.len()),
+        skip_depth: 0,
+        row_cells: 0,
+        table_rows: 0,
+        pending_space: false,
+    };
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        match rest.find('<') {
+            Some(0) => match rest.find('>') {
+                Some(end) => {
+                    renderer.tag(&rest[1..end]);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    renderer.text(rest);
+                    rest = "";
+                }
+            },
+            Some(start) => {
+                renderer.text(&rest[..start]);
+                rest = &rest[start..];
+            }
+            None => {
+                renderer.text(rest);
+                rest = "";
+            }
+        }
+    }
+
+    renderer.finish()
+}
+
+struct Renderer {
+    markdown: bool,
+    out: String,
+    skip_depth: usize,
+    row_cells: usize,
+    /// Rows closed in the current table, to add the Markdown header separator after the first
+    table_rows: usize,
+    pending_space: bool,
+}
+
+impl Renderer {
+    fn text(&mut self, text: &str) {
+        if self.skip_depth > 0 {
+            return;
+        }
+
+        let text = decode_entities(text);
+        if text.starts_with(char::is_whitespace) {
+            self.pending_space = true;
+        }
+        for word in text.split_whitespace() {
+            self.flush_space();
+            self.out.push_str(word);
+            self.pending_space = true;
+        }
+        self.pending_space = text.ends_with(char::is_whitespace);
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if tag.starts_with('!') || tag.starts_with('?') {
+            return;
+        }
+
+        let is_closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if SKIPPED_TAGS.contains(&name.as_str()) {
+            match is_closing {
+                true => self.skip_depth = self.skip_depth.saturating_sub(1),
+                false => self.skip_depth += 1,
+            }
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+
+        match (name.as_str(), is_closing) {
+            ("br", _) => self.newline(),
+            ("tr", false) => {
+                self.newline();
+                self.row_cells = 0;
+            }
+            ("table", false) => {
+                self.table_rows = 0;
+                self.paragraph();
+            }
+            ("tr", true) if self.markdown && self.row_cells > 0 => {
+                self.push_inline(" |");
+                if self.table_rows == 0 {
+                    self.newline();
+                    self.push_separator(&format!("|{}", " --- |".repeat(self.row_cells)));
+                }
+                self.table_rows += 1;
+            }
+            ("td" | "th", false) => {
+                match (self.markdown, self.row_cells) {
+                    (true, 0) => self.push_separator("| "),
+                    (true, _) => self.push_separator(" | "),
+                    (false, 0) => self.pending_space = false,
+                    (false, _) => self.push_separator("\t"),
+                }
+                self.row_cells += 1;
+            }
+            ("li", false) => {
+                self.newline();
+                if self.markdown {
+                    self.push_separator("- ");
+                }
+            }
+            ("b" | "strong", _) if self.markdown => self.push_inline("**"),
+            ("i" | "em", _) if self.markdown => self.push_inline("_"),
+            (heading, false) if self.markdown && is_heading(heading) => {
+                self.paragraph();
+                let level = heading[1..].parse().unwrap_or(1);
+                self.push_separator(&format!("{} ", "#".repeat(level)));
+            }
+            (block, _) if BLOCK_TAGS.contains(&block) => self.paragraph(),
+            _ => {}
+        }
+    }
+
+    fn push_inline(&mut self, value: &str) {
+        self.flush_space();
+        self.out.push_str(value);
+    }
+
+    fn push_separator(&mut self, value: &str) {
+        self.pending_space = false;
+        self.out.push_str(value);
+    }
+
+    fn flush_space(&mut self) {
+        if self.pending_space && !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+        self.pending_space = false;
+    }
+
+    fn newline(&mut self) {
+        self.pending_space = false;
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn paragraph(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn finish(self) -> String {
+        self.out
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .split("\n\n\n")
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            .trim()
+            .to_string()
+    }
+}
+
+fn is_heading(name: &str) -> bool {
+    name.len() == 2 && name.starts_with('h') && name[1..].parse::<u8>().is_ok_and(|l| l <= 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{parse_tsv, TXT_HEADER};
+
+    use super::*;
+
+    #[test]
+    fn it_decodes_fs_txt() {
+        let txt: FsTxt = parse_tsv(
+            TXT_HEADER,
+            &["a\tLeasesTextBlock\tus-gaap/2023\t20231231\t4\t0\ten-US\t\t\t\t0x00000000\t0\t\t1\t5000\t2048\t\t\tc1\t&lt;p&gt;Leases&lt;/p&gt;".to_string()],
+        )
+        .remove(0);
+
+        assert_eq!(txt.decoded_value(), "<p>Leases</p>");
+        assert_eq!(txt.plain_text(), "Leases");
+        assert!(txt.is_truncated());
+    }
+
+    #[test]
+    fn it_unescapes_values() {
+        assert_eq!(unescape("a\\tb\\nc\\\\d"), "a\tb\nc\\d");
+        assert_eq!(
+            unescape("&lt;p&gt;Fish &amp; Chips&#8217;s &#x26; co&lt;/p&gt;"),
+            "<p>Fish & Chips’s & co</p>"
+        );
+        assert_eq!(
+            decode_entities("AT&T &unknown; 5 &lt 6"),
+            "AT&T &unknown; 5 &lt 6"
+        );
+    }
+
+    #[test]
+    fn it_converts_html_to_text() {
+        let html = "<div><p>Revenue <b>grew</b>&nbsp;10%.</p><style>p {}</style><ul><li>One</li><li>Two</li></ul></div>";
+
+        assert_eq!(html_to_text(html, false), "Revenue grew 10%.\n\nOne\nTwo");
+        assert_eq!(
+            html_to_text(html, true),
+            "Revenue **grew** 10%.\n\n- One\n- Two"
+        );
+    }
+
+    #[test]
+    fn it_converts_tables() {
+        let html = "<table><tr><td>Year</td><td>Revenue</td></tr><tr><td>2023</td><td>100</td></tr></table>";
+
+        assert_eq!(html_to_text(html, false), "Year\tRevenue\n2023\t100");
+        assert_eq!(
+            html_to_text(html, true),
+            "| Year | Revenue |\n| --- | --- |\n| 2023 | 100 |"
+        );
+    }
+
+    #[test]
+    fn it_converts_headings() {
+        assert_eq!(
+            html_to_text("<h2>Leases</h2><p>Operating leases</p>", true),
+            "## Leases\n\nOperating leases"
+        );
+    }
+}
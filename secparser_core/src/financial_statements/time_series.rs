@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+use super::date::{self, DateInterval};
+use super::filing::FsFiling;
+use super::statement::NO_DIMENSIONS_HASH;
+
+/// How a quarterly value was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Derivation {
+    /// Reported as a 1-quarter duration
+    Reported,
+    /// Difference of consecutive year-to-date values, e.g. Q2 = 6M - Q1
+    YearToDate,
+    /// Annual value minus the 9-month year-to-date value
+    ImpliedQ4,
+    /// Only a year-to-date value was reported and the previous one is missing
+    Missing,
+}
+
+/// Discrete quarterly value of a tag, with the trailing twelve months ending on the same date
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarterlyValue {
+    pub cik: usize,
+    pub tag: String,
+    pub uom: String,
+    #[serde(with = "date::yyyymmdd")]
+    pub ddate: NaiveDate,
+    pub value: Option<f64>,
+    pub derivation: Derivation,
+    /// Reported 4-quarter value, or the sum of the last four quarters when they are all known
+    pub ttm: Option<f64>,
+    /// Quarters of the trailing twelve months without a value
+    pub ttm_missing_quarters: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct SeriesKey {
+    cik: usize,
+    tag: String,
+    uom: String,
+}
+
+/// Values keyed by `(ddate, qtrs)`, with the acceptance time of the filing they came from
+type Durations = BTreeMap<(NaiveDate, u16), (NaiveDateTime, f64)>;
+
+/// Duration values per CIK, tag and unit, collected from filings
+#[derive(Debug, Default)]
+pub struct TimeSeries {
+    tags: HashSet<String>,
+    durations: BTreeMap<SeriesKey, Durations>,
+}
+
+impl TimeSeries {
+    /// Collects only the given tags, or all tags when empty
+    pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(tags: I) -> Self {
+        Self {
+            tags: tags.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds the consolidated duration values of a filing. When several filings report the same
+    /// period, the most recently accepted value is kept.
+    pub fn add_filing(&mut self, filing: &FsFiling) {
+        let nums = filing.nums.iter().filter(|n| {
+            n.dimh == NO_DIMENSIONS_HASH
+                && n.coreg.is_empty()
+                && (self.tags.is_empty() || self.tags.contains(&n.tag))
+        });
+
+        for num in nums {
+            let (Some(value), Some(qtrs @ 1..=4)) = (num.value, num.qtrs) else {
+                continue;
+            };
+
+            let key = SeriesKey {
+                cik: filing.sub.cik,
+                tag: num.tag.to_string(),
+                uom: num.uom.to_string(),
+            };
+            let accepted = filing.sub.accepted;
+            self.durations
+                .entry(key)
+                .or_default()
+                .entry((num.ddate, qtrs))
+                .and_modify(|v| {
+                    if accepted > v.0 {
+                        *v = (accepted, value);
+                    }
+                })
+                .or_insert((accepted, value));
+        }
+    }
+
+    /// Discrete quarters for every quarter end with a reported duration, sorted by CIK, tag,
+    /// unit and date
+    pub fn quarters(&self) -> Vec<QuarterlyValue> {
+        let mut result = Vec::new();
+
+        for (key, durations) in &self.durations {
+            let value_of = |ddate: NaiveDate, qtrs: u16| durations.get(&(ddate, qtrs)).map(|v| v.1);
+            let mut ends = durations
+                .keys()
+                .map(|(ddate, _)| *ddate)
+                .collect::<Vec<_>>();
+            ends.dedup();
+
+            let mut quarters = BTreeMap::new();
+            for ddate in ends {
+                let quarter = match value_of(ddate, 1) {
+                    Some(value) => (Some(value), Derivation::Reported),
+                    None => Self::derive_quarter(ddate, value_of),
+                };
+                quarters.insert(ddate, quarter);
+            }
+
+            for (ddate, (value, derivation)) in &quarters {
+                let (ttm, ttm_missing_quarters) = match value_of(*ddate, 4) {
+                    Some(value) => (Some(value), 0),
+                    None => Self::trailing_sum(*ddate, &quarters),
+                };
+
+                result.push(QuarterlyValue {
+                    cik: key.cik,
+                    tag: key.tag.to_string(),
+                    uom: key.uom.to_string(),
+                    ddate: *ddate,
+                    value: *value,
+                    derivation: *derivation,
+                    ttm,
+                    ttm_missing_quarters,
+                });
+            }
+        }
+
+        result
+    }
+
+    fn derive_quarter(
+        ddate: NaiveDate,
+        value_of: impl Fn(NaiveDate, u16) -> Option<f64>,
+    ) -> (Option<f64>, Derivation) {
+        let Some(previous_end) = previous_quarter_end(ddate) else {
+            return (None, Derivation::Missing);
+        };
+
+        for qtrs in 2..=4 {
+            let Some(year_to_date) = value_of(ddate, qtrs) else {
+                continue;
+            };
+            if let Some(previous) = value_of(previous_end, qtrs - 1) {
+                let derivation = match qtrs {
+                    4 => Derivation::ImpliedQ4,
+                    _ => Derivation::YearToDate,
+                };
+                return (Some(year_to_date - previous), derivation);
+            }
+        }
+
+        (None, Derivation::Missing)
+    }
+
+    fn trailing_sum(
+        ddate: NaiveDate,
+        quarters: &BTreeMap<NaiveDate, (Option<f64>, Derivation)>,
+    ) -> (Option<f64>, u8) {
+        let mut sum = 0.0;
+        let mut missing = 0;
+        let mut end = Some(ddate);
+
+        for _ in 0..4 {
+            match end.and_then(|e| quarters.get(&e)).and_then(|q| q.0) {
+                Some(value) => sum += value,
+                None => missing += 1,
+            }
+            end = end.and_then(previous_quarter_end);
+        }
+
+        match missing {
+            0 => (Some(sum), 0),
+            _ => (None, missing),
+        }
+    }
+}
+
+/// `ddate` values are rounded to month ends, so the previous quarter ends 3 months earlier
+fn previous_quarter_end(ddate: NaiveDate) -> Option<NaiveDate> {
+    DateInterval::from_ddate_qtrs(ddate, 1).and_then(|i| i.start.pred_opt())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{fs_filing, num_line, SubLine};
+
+    use super::*;
+
+    fn filing(adsh: &str, form: &str, period: &str, nums: &[(&str, u16, f64)]) -> FsFiling {
+        let mut sub = SubLine::new(adsh, 1, form);
+        sub.period = period;
+        let nums = nums
+            .iter()
+            .map(|(ddate, qtrs, value)| num_line(adsh, "Revenues", ddate, *qtrs, "USD", *value))
+            .collect::<Vec<_>>();
+
+        fs_filing(sub, &nums, &[], &[])
+    }
+
+    #[test]
+    fn it_derives_quarters() {
+        let mut series = TimeSeries::new(["Revenues"]);
+        series.add_filing(&filing("q1", "10-Q", "20230331", &[("20230331", 1, 10.0)]));
+        series.add_filing(&filing("q2", "10-Q", "20230630", &[("20230630", 2, 25.0)]));
+        series.add_filing(&filing(
+            "q3",
+            "10-Q",
+            "20230930",
+            &[("20230930", 1, 20.0), ("20230930", 3, 45.0)],
+        ));
+        series.add_filing(&filing("k", "10-K", "20231231", &[("20231231", 4, 70.0)]));
+        series.add_filing(&filing("q1b", "10-Q", "20240331", &[("20240331", 1, 12.0)]));
+        series.add_filing(&filing("q3b", "10-Q", "20240930", &[("20240930", 3, 50.0)]));
+
+        let quarters = series.quarters();
+        let summary = quarters
+            .iter()
+            .map(|q| (q.ddate.to_string(), q.value, q.derivation, q.ttm))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("2023-03-31".into(), Some(10.0), Derivation::Reported, None),
+                (
+                    "2023-06-30".into(),
+                    Some(15.0),
+                    Derivation::YearToDate,
+                    None
+                ),
+                ("2023-09-30".into(), Some(20.0), Derivation::Reported, None),
+                (
+                    "2023-12-31".into(),
+                    Some(25.0),
+                    Derivation::ImpliedQ4,
+                    Some(70.0)
+                ),
+                (
+                    "2024-03-31".into(),
+                    Some(12.0),
+                    Derivation::Reported,
+                    Some(72.0)
+                ),
+                ("2024-09-30".into(), None, Derivation::Missing, None),
+            ]
+        );
+        assert_eq!(quarters[0].ttm_missing_quarters, 3);
+        assert_eq!(quarters[5].ttm_missing_quarters, 2);
+    }
+}
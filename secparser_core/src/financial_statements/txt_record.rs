@@ -1,13 +1,16 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use super::date::{self, DateInterval};
 use super::record::FsRecord;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FsTxt {
     pub adsh: String,
     pub tag: String,
     pub version: String,
-    pub ddate: String,
+    #[serde(with = "date::yyyymmdd")]
+    pub ddate: NaiveDate,
     pub qtrs: Option<u16>,
     pub iprx: Option<u16>,
     pub lang: String,
@@ -26,6 +29,13 @@ pub struct FsTxt {
     pub value: String,
 }
 
+impl FsTxt {
+    /// Start and end dates of the period this value covers
+    pub fn interval(&self) -> Option<DateInterval> {
+        DateInterval::from_ddate_qtrs(self.ddate, self.qtrs?)
+    }
+}
+
 impl FsRecord for FsTxt {
     fn csv_filename() -> String {
         "txt.tsv".to_string()
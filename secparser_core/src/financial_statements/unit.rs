@@ -0,0 +1,463 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use snafu::{Location, ResultExt, Snafu};
+
+use super::date::{self, DateInterval};
+use super::num_record::FsNum;
+
+/// ISO 4217 currency codes, including codes replaced since 2000 that older filings still use
+const ISO_4217_CODES: [&str; 170] = [
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BYR", "BZD",
+    "CAD", "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUC", "CUP", "CVE", "CZK", "DJF", "DKK",
+    "DOP", "DZD", "EEK", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP",
+    "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HRK", "HTG", "HUF", "IDR", "ILS", "INR", "IQD",
+    "IRR", "ISK", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD",
+    "KZT", "LAK", "LBP", "LKR", "LRD", "LSL", "LTL", "LVL", "LYD", "MAD", "MDL", "MGA", "MKD",
+    "MMK", "MNT", "MOP", "MRO", "MRU", "MUR", "MVR", "MWK", "MXN", "MYR", "MZN", "NAD", "NGN",
+    "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR",
+    "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SKK", "SLE",
+    "SLL", "SOS", "SRD", "SSP", "STD", "STN", "SVC", "SYP", "SZL", "THB", "TJS", "TMT", "TND",
+    "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS", "VED", "VEF", "VES",
+    "VND", "VUV", "WST", "XAF", "XCD", "XCG", "XOF", "XPF", "YER", "ZAR", "ZMK", "ZMW", "ZWG",
+    "ZWL",
+];
+
+/// Scale words of custom units such as `USD_thousands` or `EURm`, with their power of ten
+const SCALES: [(&str, u8); 10] = [
+    ("thousands", 3),
+    ("thousand", 3),
+    ("k", 3),
+    ("millions", 6),
+    ("million", 6),
+    ("mm", 6),
+    ("m", 6),
+    ("billions", 9),
+    ("billion", 9),
+    ("bn", 9),
+];
+
+#[derive(Debug, Snafu)]
+pub enum UnitError {
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to read FX rates from {filepath:?}"))]
+    Csv {
+        source: csv::Error,
+        filepath: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitKind {
+    /// A currency, e.g. `USD`
+    Currency,
+    /// A number of shares
+    Shares,
+    /// A dimensionless number, e.g. a percentage
+    Pure,
+    /// A currency per share, e.g. `USD/shares`
+    PerShare,
+    /// Any other quotient, e.g. `USD/bbl`
+    Ratio,
+    /// Any other measure, e.g. `sqft`
+    Other,
+}
+
+/// Parsed `FsNum::uom`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Unit {
+    /// Measure of the numerator without its scale, e.g. `USD` for `USD_thousands`
+    pub numerator: String,
+    pub denominator: Option<String>,
+    /// Power of ten the values are expressed in, e.g. 3 for `USD_thousands`
+    pub scale: u8,
+}
+
+impl Unit {
+    /// Parses a uom, recognizing custom scaled units of currencies and shares such as
+    /// `USD_thousands`, `Millions of USD` or `EURm`
+    pub fn parse(uom: &str) -> Self {
+        let (numerator, denominator) = match uom.split_once('/') {
+            Some((numerator, denominator)) => (numerator, Some(denominator.trim().to_string())),
+            None => (uom, None),
+        };
+        let (numerator, scale) = split_scale(numerator.trim());
+
+        Self {
+            numerator: numerator.to_string(),
+            denominator,
+            scale,
+        }
+    }
+
+    /// Multiplier from values in this unit to values in the unscaled unit
+    pub fn factor(&self) -> f64 {
+        10f64.powi(self.scale.into())
+    }
+
+    /// The same unit without its scale, e.g. `USD` for `USD_thousands`
+    pub fn unscaled(&self) -> Self {
+        Self {
+            scale: 0,
+            ..self.clone()
+        }
+    }
+
+    pub fn kind(&self) -> UnitKind {
+        match (&self.denominator, self.currency()) {
+            (None, Some(_)) => UnitKind::Currency,
+            (None, None) if is_shares(&self.numerator) => UnitKind::Shares,
+            (None, None) if self.numerator.eq_ignore_ascii_case("pure") => UnitKind::Pure,
+            (None, None) => UnitKind::Other,
+            (Some(d), Some(_)) if is_shares(d) => UnitKind::PerShare,
+            (Some(_), _) => UnitKind::Ratio,
+        }
+    }
+
+    /// ISO 4217 code of the numerator, if it is a currency
+    pub fn currency(&self) -> Option<&str> {
+        is_currency(&self.numerator).then_some(self.numerator.as_str())
+    }
+
+    pub fn is_monetary(&self) -> bool {
+        matches!(self.kind(), UnitKind::Currency | UnitKind::PerShare)
+    }
+
+    /// The same unit with the currency replaced, e.g. `EUR/shares` to `USD/shares`
+    pub fn with_currency(&self, currency: &str) -> Self {
+        match self.currency() {
+            Some(_) => Self {
+                numerator: currency.to_string(),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    /// Scaled units are written with a suffix, e.g. `USD_thousands`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.numerator)?;
+        match self.scale {
+            0 => {}
+            3 => f.write_str("_thousands")?,
+            6 => f.write_str("_millions")?,
+            9 => f.write_str("_billions")?,
+            scale => write!(f, "_e{scale}")?,
+        }
+        if let Some(denominator) = &self.denominator {
+            write!(f, "/{denominator}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_currency(value: &str) -> bool {
+    ISO_4217_CODES.binary_search(&value).is_ok()
+}
+
+/// Splits a scale word before or after a currency or `shares`, e.g. `USD_thousands`
+fn split_scale(value: &str) -> (&str, u8) {
+    let is_measure = |v: &str| is_currency(v) || is_shares(v);
+    let separators: &[char] = &['_', '-', ' '];
+
+    for (word, scale) in SCALES {
+        if let Some(head) = strip_suffix_ignore_case(value, word) {
+            let base = head.trim_end_matches(separators);
+            if is_measure(base) {
+                return (base, scale);
+            }
+        }
+
+        if let Some(tail) = strip_prefix_ignore_case(value, word) {
+            let base = tail.trim_start_matches(separators);
+            let base = strip_prefix_ignore_case(base, "of")
+                .map(|b| b.trim_start_matches(separators))
+                .unwrap_or(base);
+            if is_measure(base) {
+                return (base, scale);
+            }
+        }
+    }
+
+    (value, 0)
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = value.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &value[prefix.len()..])
+}
+
+fn strip_suffix_ignore_case<'a>(value: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = value.len().checked_sub(suffix.len())?;
+    let tail = value.get(split..)?;
+    tail.eq_ignore_ascii_case(suffix).then(|| &value[..split])
+}
+
+fn is_shares(value: &str) -> bool {
+    value.eq_ignore_ascii_case("shares")
+}
+
+impl FsNum {
+    pub fn unit(&self) -> Unit {
+        Unit::parse(&self.uom)
+    }
+
+    /// The value in the unscaled unit, e.g. 1500 `USD` for 1.5 `USD_thousands`
+    pub fn unscaled(&self) -> FsNum {
+        let unit = self.unit();
+        if unit.scale == 0 {
+            return self.clone();
+        }
+
+        FsNum {
+            uom: unit.unscaled().to_string(),
+            value: self.value.map(|v| v * unit.factor()),
+            ..self.clone()
+        }
+    }
+}
+
+/// Unit filters on streams of `FsNum`
+pub trait FsNumUnitExt: Iterator<Item = FsNum> + Sized {
+    fn with_unit_kind(self, kind: UnitKind) -> impl Iterator<Item = FsNum> {
+        self.filter(move |num| num.unit().kind() == kind)
+    }
+
+    /// Keeps monetary values in the given currency, including per-share values
+    fn with_currency<'a>(self, currency: &'a str) -> impl Iterator<Item = FsNum> + 'a
+    where
+        Self: 'a,
+    {
+        self.filter(move |num| num.unit().currency() == Some(currency))
+    }
+}
+
+impl<I: Iterator<Item = FsNum>> FsNumUnitExt for I {}
+
+#[derive(Debug, Deserialize)]
+struct FxRateRecord {
+    currency: String,
+    #[serde(with = "date::iso_date")]
+    date: NaiveDate,
+    rate: f64,
+}
+
+/// Exchange rates into a single target currency.
+///
+/// Loaded from a CSV file with a `currency,date,rate` header, where `date` is `YYYY-MM-DD` and
+/// `rate` is the amount of the target currency for one unit of `currency`.
+#[derive(Debug, Clone)]
+pub struct FxRates {
+    pub target: String,
+    rates: HashMap<String, BTreeMap<NaiveDate, f64>>,
+}
+
+impl FxRates {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            rates: HashMap::new(),
+        }
+    }
+
+    pub fn from_path(filepath: &Path, target: &str) -> Result<Self, UnitError> {
+        let mut reader = ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(filepath)
+            .context(CsvSnafu { filepath })?;
+
+        let mut fx_rates = Self::new(target);
+        for record in reader.deserialize() {
+            let record: FxRateRecord = record.context(CsvSnafu { filepath })?;
+            fx_rates.insert(&record.currency, record.date, record.rate);
+        }
+
+        Ok(fx_rates)
+    }
+
+    pub fn insert(&mut self, currency: &str, date: NaiveDate, rate: f64) {
+        self.rates
+            .entry(currency.to_string())
+            .or_default()
+            .insert(date, rate);
+    }
+
+    /// The latest rate on or before the date, or 1 for the target currency
+    pub fn rate(&self, currency: &str, date: NaiveDate) -> Option<f64> {
+        if currency == self.target {
+            return Some(1.0);
+        }
+
+        self.rates
+            .get(currency)?
+            .range(..=date)
+            .next_back()
+            .map(|(_, rate)| *rate)
+    }
+
+    /// Mean of the rates within the interval, falling back to the rate at its end when there
+    /// are none
+    pub fn average_rate(&self, currency: &str, interval: DateInterval) -> Option<f64> {
+        if currency == self.target {
+            return Some(1.0);
+        }
+
+        let rates = self
+            .rates
+            .get(currency)?
+            .range(interval.start..=interval.end)
+            .map(|(_, rate)| *rate)
+            .collect::<Vec<_>>();
+        match rates.is_empty() {
+            true => self.rate(currency, interval.end),
+            false => Some(rates.iter().sum::<f64>() / rates.len() as f64),
+        }
+    }
+
+    /// Converts monetary values into the target currency, unscaled.
+    ///
+    /// Instant values use the rate at `ddate`, and duration values (e.g. revenue) the average
+    /// rate over their period, as income statement items are translated at average rates.
+    /// Non-monetary values are returned unchanged, and values without a rate are `None`.
+    pub fn convert(&self, num: &FsNum) -> Option<FsNum> {
+        let unit = num.unit();
+        let Some(currency) = unit.currency() else {
+            return Some(num.clone());
+        };
+        let rate = match num.interval() {
+            Some(interval) if !interval.is_instant() => self.average_rate(currency, interval)?,
+            _ => self.rate(currency, num.ddate)?,
+        };
+
+        Some(FsNum {
+            uom: unit.with_currency(&self.target).unscaled().to_string(),
+            value: num.value.map(|v| v * unit.factor() * rate),
+            ..num.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::financial_statements::test_utils::{num_line, parse_tsv, NUM_HEADER};
+
+    use super::*;
+
+    #[test]
+    fn it_parses_units() {
+        let kinds = [
+            "USD",
+            "EUR",
+            "shares",
+            "USD/shares",
+            "pure",
+            "USD/bbl",
+            "sqft",
+            "USD_thousands",
+            "Millions of EUR",
+            "shares_thousands",
+            "XYZ",
+        ]
+        .map(|uom| Unit::parse(uom).kind());
+
+        assert_eq!(
+            kinds,
+            [
+                UnitKind::Currency,
+                UnitKind::Currency,
+                UnitKind::Shares,
+                UnitKind::PerShare,
+                UnitKind::Pure,
+                UnitKind::Ratio,
+                UnitKind::Other,
+                UnitKind::Currency,
+                UnitKind::Currency,
+                UnitKind::Shares,
+                UnitKind::Other,
+            ]
+        );
+        assert_eq!(Unit::parse("EURm").currency(), Some("EUR"));
+        assert_eq!(Unit::parse("EURm").factor(), 1e6);
+        assert_eq!(Unit::parse("Millions of EUR").to_string(), "EUR_millions");
+        assert_eq!(Unit::parse("sqm").scale, 0);
+        assert_eq!(
+            Unit::parse("EUR/shares").with_currency("USD").to_string(),
+            "USD/shares"
+        );
+    }
+
+    #[test]
+    fn it_parses_non_ascii_units() {
+        for uom in ["m²", "€", "k€", "€m", "USD/m³"] {
+            let unit = Unit::parse(uom);
+            assert_eq!(unit.scale, 0, "{uom}");
+            assert_eq!(unit.to_string(), uom);
+        }
+    }
+
+    #[test]
+    fn it_converts_currencies() -> Result<(), UnitError> {
+        let filepath = std::env::temp_dir().join("secparser_fx_rates.csv");
+        std::fs::write(
+            &filepath,
+            "currency,date,rate\nEUR,2023-06-30,1.0\nEUR,2023-12-29,1.5\nEUR,2024-06-28,2.0\n",
+        )?;
+        let fx_rates = FxRates::from_path(&filepath, "USD")?;
+
+        let nums = parse_tsv::<FsNum>(
+            NUM_HEADER,
+            &[
+                num_line("a", "Revenues", "20231231", 4, "EUR", 100.0),
+                num_line(
+                    "a",
+                    "EarningsPerShareBasic",
+                    "20231231",
+                    4,
+                    "EUR/shares",
+                    2.0,
+                ),
+                num_line("a", "SharesOutstanding", "20231231", 0, "shares", 5.0),
+                num_line("a", "Revenues", "20221231", 4, "EUR", 90.0),
+                num_line("a", "Assets", "20231231", 0, "EUR_thousands", 2.0),
+            ],
+        );
+
+        let converted = nums
+            .iter()
+            .map(|n| fx_rates.convert(n).map(|n| (n.uom, n.value)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            converted,
+            vec![
+                Some(("USD".to_string(), Some(125.0))),
+                Some(("USD/shares".to_string(), Some(2.5))),
+                Some(("shares".to_string(), Some(5.0))),
+                None,
+                Some(("USD".to_string(), Some(3000.0))),
+            ]
+        );
+        assert_eq!(nums[4].unscaled().value, Some(2000.0));
+        assert_eq!(nums.into_iter().with_currency("EUR").count(), 4);
+
+        Ok(())
+    }
+}
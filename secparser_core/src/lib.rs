@@ -2,5 +2,7 @@ pub mod cik_lookup;
 pub mod data_source;
 pub mod downloader;
 pub mod financial_statements;
+pub mod sic;
+pub mod submissions;
 pub mod traits;
 pub mod zip_csv_records;
@@ -0,0 +1,470 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use snafu::{Location, ResultExt, Snafu};
+
+use crate::financial_statements::sub_record::FsSub;
+use crate::submissions::record::CompanySubmissions;
+
+const DEFAULT_CODES: &str = include_str!("../data/sic_codes.csv");
+
+/// Codes the SEC assigns outside the SIC hierarchy: American depositary receipts (8880),
+/// foreign governments (8888) and non-operating establishments such as shells (9995).
+/// 8900 and 9721 are standard codes of major groups 89 and 97.
+pub const SEC_PSEUDO_CODES: [u16; 3] = [8880, 8888, 9995];
+
+#[derive(Debug, Snafu)]
+pub enum SicError {
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to read SIC codes from {filepath:?}"))]
+    Csv {
+        source: csv::Error,
+        filepath: PathBuf,
+    },
+}
+
+/// Top level of the SIC hierarchy, spanning a range of major groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Division {
+    pub letter: char,
+    pub name: &'static str,
+    pub major_groups: (u16, u16),
+}
+
+pub const DIVISIONS: [Division; 11] = [
+    Division {
+        letter: 'A',
+        name: "Agriculture, Forestry, and Fishing",
+        major_groups: (1, 9),
+    },
+    Division {
+        letter: 'B',
+        name: "Mining",
+        major_groups: (10, 14),
+    },
+    Division {
+        letter: 'C',
+        name: "Construction",
+        major_groups: (15, 17),
+    },
+    Division {
+        letter: 'D',
+        name: "Manufacturing",
+        major_groups: (20, 39),
+    },
+    Division {
+        letter: 'E',
+        name: "Transportation, Communications, Electric, Gas, and Sanitary Services",
+        major_groups: (40, 49),
+    },
+    Division {
+        letter: 'F',
+        name: "Wholesale Trade",
+        major_groups: (50, 51),
+    },
+    Division {
+        letter: 'G',
+        name: "Retail Trade",
+        major_groups: (52, 59),
+    },
+    Division {
+        letter: 'H',
+        name: "Finance, Insurance, and Real Estate",
+        major_groups: (60, 67),
+    },
+    Division {
+        letter: 'I',
+        name: "Services",
+        major_groups: (70, 89),
+    },
+    Division {
+        letter: 'J',
+        name: "Public Administration",
+        major_groups: (91, 97),
+    },
+    Division {
+        letter: 'K',
+        name: "Nonclassifiable Establishments",
+        major_groups: (99, 99),
+    },
+];
+
+/// Names of the two-digit major groups
+const MAJOR_GROUPS: [(u16, &str); 83] = [
+    (1, "Agricultural Production - Crops"),
+    (2, "Agricultural Production - Livestock and Animal Specialties"),
+    (7, "Agricultural Services"),
+    (8, "Forestry"),
+    (9, "Fishing, Hunting, and Trapping"),
+    (10, "Metal Mining"),
+    (12, "Coal Mining"),
+    (13, "Oil and Gas Extraction"),
+    (14, "Mining and Quarrying of Nonmetallic Minerals, Except Fuels"),
+    (15, "Building Construction General Contractors and Operative Builders"),
+    (16, "Heavy Construction other than Building Construction Contractors"),
+    (17, "Construction Special Trade Contractors"),
+    (20, "Food and Kindred Products"),
+    (21, "Tobacco Products"),
+    (22, "Textile Mill Products"),
+    (23, "Apparel and other Finished Products Made from Fabrics and Similar Materials"),
+    (24, "Lumber and Wood Products, except Furniture"),
+    (25, "Furniture and Fixtures"),
+    (26, "Paper and Allied Products"),
+    (27, "Printing, Publishing, and Allied Industries"),
+    (28, "Chemicals and Allied Products"),
+    (29, "Petroleum Refining and Related Industries"),
+    (30, "Rubber and Miscellaneous Plastics Products"),
+    (31, "Leather and Leather Products"),
+    (32, "Stone, Clay, Glass, and Concrete Products"),
+    (33, "Primary Metal Industries"),
+    (34, "Fabricated Metal Products, except Machinery and Transportation Equipment"),
+    (35, "Industrial and Commercial Machinery and Computer Equipment"),
+    (36, "Electronic and other Electrical Equipment and Components, except Computer Equipment"),
+    (37, "Transportation Equipment"),
+    (38, "Measuring, Analyzing, and Controlling Instruments; Photographic, Medical and Optical Goods; Watches and Clocks"),
+    (39, "Miscellaneous Manufacturing Industries"),
+    (40, "Railroad Transportation"),
+    (41, "Local and Suburban Transit and Interurban Highway Passenger Transportation"),
+    (42, "Motor Freight Transportation and Warehousing"),
+    (43, "United States Postal Service"),
+    (44, "Water Transportation"),
+    (45, "Transportation by Air"),
+    (46, "Pipelines, except Natural Gas"),
+    (47, "Transportation Services"),
+    (48, "Communications"),
+    (49, "Electric, Gas, and Sanitary Services"),
+    (50, "Wholesale Trade - Durable Goods"),
+    (51, "Wholesale Trade - Nondurable Goods"),
+    (52, "Building Materials, Hardware, Garden Supply, and Mobile Home Dealers"),
+    (53, "General Merchandise Stores"),
+    (54, "Food Stores"),
+    (55, "Automotive Dealers and Gasoline Service Stations"),
+    (56, "Apparel and Accessory Stores"),
+    (57, "Home Furniture, Furnishings, and Equipment Stores"),
+    (58, "Eating and Drinking Places"),
+    (59, "Miscellaneous Retail"),
+    (60, "Depository Institutions"),
+    (61, "Nondepository Credit Institutions"),
+    (62, "Security and Commodity Brokers, Dealers, Exchanges, and Services"),
+    (63, "Insurance Carriers"),
+    (64, "Insurance Agents, Brokers, and Service"),
+    (65, "Real Estate"),
+    (67, "Holding and other Investment Offices"),
+    (70, "Hotels, Rooming Houses, Camps, and other Lodging Places"),
+    (72, "Personal Services"),
+    (73, "Business Services"),
+    (75, "Automotive Repair, Services, and Parking"),
+    (76, "Miscellaneous Repair Services"),
+    (78, "Motion Pictures"),
+    (79, "Amusement and Recreation Services"),
+    (80, "Health Services"),
+    (81, "Legal Services"),
+    (82, "Educational Services"),
+    (83, "Social Services"),
+    (84, "Museums, Art Galleries, and Botanical and Zoological Gardens"),
+    (86, "Membership Organizations"),
+    (87, "Engineering, Accounting, Research, Management, and Related Services"),
+    (88, "Private Households"),
+    (89, "Miscellaneous Services"),
+    (91, "Executive, Legislative, and General Government, except Finance"),
+    (92, "Justice, Public Order, and Safety"),
+    (93, "Public Finance, Taxation, and Monetary Policy"),
+    (94, "Administration of Human Resource Programs"),
+    (95, "Administration of Environmental Quality and Housing Programs"),
+    (96, "Administration of Economic Programs"),
+    (97, "National Security and International Affairs"),
+    (99, "Nonclassifiable Establishments"),
+];
+
+/// Parses a SIC code such as `3571` or `0100`, returning `None` for blank or invalid values
+pub fn parse_sic(value: &str) -> Option<u16> {
+    value.trim().parse().ok()
+}
+
+/// Two-digit major group of a SIC code, e.g. 35 for 3571
+pub fn major_group(code: u16) -> u16 {
+    code / 100
+}
+
+/// Codes of a major group, e.g. 3500..=3599 for 35, or `None` past the last major group
+fn major_group_codes(major_group: u16) -> Option<RangeInclusive<u16>> {
+    let start = major_group.checked_mul(100)?;
+
+    Some(start..=start.saturating_add(99))
+}
+
+pub fn major_group_name(major_group: u16) -> Option<&'static str> {
+    MAJOR_GROUPS
+        .iter()
+        .find(|(group, _)| *group == major_group)
+        .map(|(_, name)| *name)
+}
+
+/// Division of a SIC code, or `None` for codes outside the hierarchy such as `SEC_PSEUDO_CODES`
+pub fn division(code: u16) -> Option<&'static Division> {
+    if SEC_PSEUDO_CODES.contains(&code) {
+        return None;
+    }
+    let group = major_group(code);
+
+    DIVISIONS
+        .iter()
+        .find(|d| (d.major_groups.0..=d.major_groups.1).contains(&group))
+}
+
+/// A row of the SEC SIC code list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SicCode {
+    pub code: u16,
+    /// SEC Division of Corporation Finance office reviewing the filings
+    pub office: String,
+    pub description: String,
+}
+
+impl SicCode {
+    pub fn major_group(&self) -> u16 {
+        major_group(self.code)
+    }
+
+    pub fn division(&self) -> Option<&'static Division> {
+        division(self.code)
+    }
+}
+
+/// SIC codes by code
+#[derive(Debug, Clone)]
+pub struct SicTable {
+    codes: BTreeMap<u16, SicCode>,
+}
+
+impl Default for SicTable {
+    /// The SEC list shipped in `data/sic_codes.csv`
+    fn default() -> Self {
+        let mut reader = ReaderBuilder::new().from_reader(DEFAULT_CODES.as_bytes());
+        let codes = reader
+            .deserialize()
+            .collect::<Result<Vec<SicCode>, _>>()
+            .unwrap_or_else(|e| panic!("Should parse default SIC codes: {e}"));
+
+        codes.into_iter().collect()
+    }
+}
+
+impl FromIterator<SicCode> for SicTable {
+    fn from_iter<I: IntoIterator<Item = SicCode>>(iter: I) -> Self {
+        Self {
+            codes: iter.into_iter().map(|c| (c.code, c)).collect(),
+        }
+    }
+}
+
+impl SicTable {
+    /// Loads codes from a CSV file with a `code,office,description` header
+    pub fn from_path(filepath: &Path) -> Result<Self, SicError> {
+        let mut reader = ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(filepath)
+            .context(CsvSnafu { filepath })?;
+
+        reader
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .context(CsvSnafu { filepath })
+    }
+
+    pub fn get(&self, code: u16) -> Option<&SicCode> {
+        self.codes.get(&code)
+    }
+
+    /// Looks up a code as found in `FsSub::sic` or `CompanySubmissions::sic`
+    pub fn parse(&self, value: &str) -> Option<&SicCode> {
+        self.get(parse_sic(value)?)
+    }
+
+    pub fn codes(&self) -> impl Iterator<Item = &SicCode> {
+        self.codes.values()
+    }
+
+    pub fn in_major_group(&self, major_group: u16) -> impl Iterator<Item = &SicCode> {
+        major_group_codes(major_group)
+            .into_iter()
+            .flat_map(|codes| self.codes.range(codes))
+            .map(|(_, c)| c)
+    }
+
+    pub fn in_division(&self, letter: char) -> impl Iterator<Item = &SicCode> {
+        self.codes
+            .values()
+            .filter(move |c| c.division().is_some_and(|d| d.letter == letter))
+    }
+
+    pub fn in_office<'a>(&'a self, office: &'a str) -> impl Iterator<Item = &'a SicCode> + 'a {
+        self.codes.values().filter(move |c| c.office == office)
+    }
+}
+
+impl FsSub {
+    pub fn sic_code(&self) -> Option<u16> {
+        parse_sic(&self.sic)
+    }
+}
+
+impl CompanySubmissions {
+    pub fn sic_code(&self) -> Option<u16> {
+        self.sic.as_deref().and_then(parse_sic)
+    }
+}
+
+/// CIKs grouped by SIC code, built from submissions data
+#[derive(Debug, Default)]
+pub struct PeerGroups {
+    by_sic: BTreeMap<u16, BTreeSet<usize>>,
+    sic_by_cik: HashMap<usize, u16>,
+}
+
+impl PeerGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a company, ignoring it if it has no SIC code
+    pub fn insert(&mut self, company: &CompanySubmissions) {
+        let Some(sic) = company.sic_code() else {
+            return;
+        };
+
+        if let Some(previous) = self.sic_by_cik.insert(company.cik, sic) {
+            if let Some(ciks) = self.by_sic.get_mut(&previous) {
+                ciks.remove(&company.cik);
+            }
+        }
+        self.by_sic.entry(sic).or_default().insert(company.cik);
+    }
+
+    pub fn sic(&self, cik: usize) -> Option<u16> {
+        self.sic_by_cik.get(&cik).copied()
+    }
+
+    /// All CIKs with the SIC code, ordered by CIK
+    pub fn ciks(&self, sic: u16) -> Vec<usize> {
+        self.by_sic
+            .get(&sic)
+            .map(|ciks| ciks.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// All CIKs with a SIC code in the major group, ordered by SIC code then CIK
+    pub fn major_group_ciks(&self, major_group: u16) -> Vec<usize> {
+        major_group_codes(major_group)
+            .into_iter()
+            .flat_map(|codes| self.by_sic.range(codes))
+            .flat_map(|(_, ciks)| ciks.iter().copied())
+            .collect()
+    }
+
+    /// Other CIKs sharing the SIC code of `cik`
+    pub fn peers(&self, cik: usize) -> Vec<usize> {
+        let Some(sic) = self.sic(cik) else {
+            return vec![];
+        };
+
+        self.ciks(sic).into_iter().filter(|c| *c != cik).collect()
+    }
+}
+
+impl<'a> FromIterator<&'a CompanySubmissions> for PeerGroups {
+    fn from_iter<I: IntoIterator<Item = &'a CompanySubmissions>>(iter: I) -> Self {
+        let mut groups = Self::new();
+        for company in iter {
+            groups.insert(company);
+        }
+        groups
+    }
+}
+
+impl FromIterator<CompanySubmissions> for PeerGroups {
+    fn from_iter<I: IntoIterator<Item = CompanySubmissions>>(iter: I) -> Self {
+        let mut groups = Self::new();
+        for company in iter {
+            groups.insert(&company);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn company(cik: usize, sic: &str) -> CompanySubmissions {
+        let json = format!(
+            r#"{{"cik": "{cik}", "name": "Company {cik}", "sic": "{sic}", "filings": {{"recent": {{}}}}}}"#
+        );
+        serde_json::from_str(&json).unwrap_or_else(|e| panic!("Should parse company: {e}"))
+    }
+
+    #[test]
+    fn it_classifies_sic_codes() {
+        let table = SicTable::default();
+
+        let computers = table.parse("3571").unwrap();
+        assert_eq!(computers.description, "ELECTRONIC COMPUTERS");
+        assert_eq!(computers.office, "Office of Technology");
+        assert_eq!(computers.major_group(), 35);
+        assert_eq!(
+            major_group_name(computers.major_group()),
+            Some("Industrial and Commercial Machinery and Computer Equipment")
+        );
+        assert_eq!(computers.division().unwrap().letter, 'D');
+
+        assert_eq!(table.parse("0100").unwrap().division().unwrap().letter, 'A');
+        assert!(table.parse("").is_none());
+        assert!(table.in_major_group(28).any(|c| c.code == 2834));
+        assert!(table
+            .in_division('H')
+            .all(|c| (6000..6800).contains(&c.code)));
+        assert_eq!(table.in_major_group(u16::MAX).count(), 0);
+    }
+
+    #[test]
+    fn it_excludes_sec_pseudo_codes_from_divisions() {
+        let table = SicTable::default();
+
+        let adr = table.parse("8880").unwrap();
+        assert_eq!(adr.description, "AMERICAN DEPOSITARY RECEIPTS");
+        assert!(adr.division().is_none());
+        assert!(division(8888).is_none());
+        assert!(division(9995).is_none());
+        assert_eq!(division(8900).unwrap().letter, 'I');
+        assert_eq!(division(9721).unwrap().letter, 'J');
+        assert!(table.in_division('I').all(|c| c.code != 8880));
+    }
+
+    #[test]
+    fn it_groups_peers() {
+        let companies = [
+            company(320193, "3571"),
+            company(1, "3571"),
+            company(2, "3572"),
+            company(3, ""),
+        ];
+        let groups = companies.iter().collect::<PeerGroups>();
+
+        assert_eq!(groups.peers(320193), vec![1]);
+        assert_eq!(groups.ciks(3571), vec![1, 320193]);
+        assert_eq!(groups.major_group_ciks(35), vec![1, 320193, 2]);
+        assert!(groups.major_group_ciks(700).is_empty());
+        assert!(groups.peers(3).is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::vec;
+
+use serde::de::DeserializeOwned;
+use snafu::ResultExt;
+use zip::ZipArchive;
+
+use crate::data_source::DataSource;
+use crate::downloader::DownloadConfig;
+
+use super::data_source::SubmissionsDataSources;
+use super::record::{
+    CompanySubmissions, DataSourceSnafu, DeserializeEntrySnafu, Filing, FilingColumns,
+    SubmissionsError, ZipSnafu,
+};
+
+/// Company files of the bulk `submissions.zip`, in archive order.
+///
+/// Continuation files are skipped by the iterator and read with `BulkSubmissions::filings`.
+pub struct BulkSubmissions {
+    archive: ZipArchive<File>,
+    filepath: PathBuf,
+    companies: vec::IntoIter<String>,
+}
+
+impl BulkSubmissions {
+    pub fn new(download_config: &DownloadConfig) -> Result<Self, SubmissionsError> {
+        let data_source = SubmissionsDataSources::bulk(download_config).context(DataSourceSnafu)?;
+
+        Self::from_data_source(&data_source)
+    }
+
+    pub fn from_data_source(data_source: &DataSource) -> Result<Self, SubmissionsError> {
+        let filepath = data_source.filepath.clone();
+        let archive = ZipArchive::new(File::open(&filepath)?).context(ZipSnafu {
+            filepath: &filepath,
+        })?;
+
+        let companies = archive
+            .file_names()
+            .filter(|name| is_company_file(name))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            archive,
+            filepath,
+            companies: companies.into_iter(),
+        })
+    }
+
+    /// Recent filings of a company followed by those of its continuation files in the archive
+    pub fn filings(
+        &mut self,
+        company: &CompanySubmissions,
+    ) -> Result<Vec<Filing>, SubmissionsError> {
+        let mut filings = company.recent_filings().collect::<Vec<_>>();
+        for file in &company.filings.files {
+            let columns: FilingColumns = self.read(&file.name)?;
+            filings.extend(columns.iter());
+        }
+
+        Ok(filings)
+    }
+
+    fn read<T: DeserializeOwned>(&mut self, name: &str) -> Result<T, SubmissionsError> {
+        let file = self.archive.by_name(name).context(ZipSnafu {
+            filepath: &self.filepath,
+        })?;
+
+        serde_json::from_reader(BufReader::new(file)).context(DeserializeEntrySnafu {
+            name,
+            filepath: &self.filepath,
+        })
+    }
+}
+
+impl Iterator for BulkSubmissions {
+    type Item = Result<CompanySubmissions, SubmissionsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.companies.next()?;
+
+        Some(self.read(&name))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.companies.size_hint()
+    }
+}
+
+impl ExactSizeIterator for BulkSubmissions {}
+
+/// `CIK##########.json`, as opposed to continuation files `CIK##########-submissions-###.json`
+fn is_company_file(name: &str) -> bool {
+    name.starts_with("CIK") && name.ends_with(".json") && !name.contains("-submissions-")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::submissions::record::tests::{COMPANY_JSON, CONTINUATION_JSON};
+    use crate::zip_csv_records::write_test_zip;
+
+    use super::*;
+
+    #[test]
+    fn it_iterates_bulk_submissions() -> Result<(), SubmissionsError> {
+        let data_source = write_test_zip(
+            "secparser_submissions.zip",
+            &[
+                ("CIK0000320193-submissions-001.json", CONTINUATION_JSON),
+                ("CIK0000320193.json", COMPANY_JSON),
+                ("CIK0000000042.json", "{}"),
+            ],
+        );
+
+        let mut bulk = BulkSubmissions::from_data_source(&data_source)?;
+        assert_eq!(bulk.len(), 2);
+
+        let company = bulk.next().unwrap()?;
+        assert_eq!(company.name, "Apple Inc.");
+        let filings = bulk.filings(&company)?;
+        assert_eq!(filings.len(), 3);
+        assert_eq!(filings[2].accession_number, "0001193125-14-383437");
+
+        assert!(matches!(
+            bulk.next(),
+            Some(Err(SubmissionsError::DeserializeEntry { .. }))
+        ));
+        assert!(bulk.next().is_none());
+
+        Ok(())
+    }
+}
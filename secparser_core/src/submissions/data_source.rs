@@ -0,0 +1,34 @@
+use crate::data_source::{DataSource, DataSourceError};
+use crate::downloader::DownloadConfig;
+
+pub struct SubmissionsDataSources;
+
+impl SubmissionsDataSources {
+    pub const BASE_URL: &'static str = "https://data.sec.gov/submissions";
+    pub const BULK_URL: &'static str =
+        "https://www.sec.gov/Archives/edgar/daily-index/bulkdata/submissions.zip";
+
+    /// Name of the submissions file of a CIK, e.g. `CIK0000320193.json`
+    pub fn company_filename(cik: usize) -> String {
+        format!("CIK{cik:010}.json")
+    }
+
+    pub fn company(
+        download_config: &DownloadConfig,
+        cik: usize,
+    ) -> Result<DataSource, DataSourceError> {
+        Self::file(download_config, &Self::company_filename(cik))
+    }
+
+    /// A file listed in `filings.files`, e.g. `CIK0000320193-submissions-001.json`
+    pub fn file(
+        download_config: &DownloadConfig,
+        name: &str,
+    ) -> Result<DataSource, DataSourceError> {
+        DataSource::new(download_config, &format!("{}/{name}", Self::BASE_URL))
+    }
+
+    pub fn bulk(download_config: &DownloadConfig) -> Result<DataSource, DataSourceError> {
+        DataSource::new(download_config, Self::BULK_URL)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod bulk;
+pub mod data_source;
+pub mod record;
@@ -0,0 +1,439 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{de, Deserialize, Deserializer};
+use snafu::{Location, ResultExt, Snafu};
+
+use crate::data_source::DataSourceError;
+use crate::downloader::DownloadConfig;
+use crate::financial_statements::date::{self, FiscalYearEnd};
+
+use super::data_source::SubmissionsDataSources;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum SubmissionsError {
+    #[snafu(display("Failed to get data source"))]
+    DataSource { source: DataSourceError },
+
+    #[snafu(display("IO error at {loc}"))]
+    #[snafu(context(false))]
+    IO {
+        source: io::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Failed to deserialize {filepath:?}"))]
+    Deserialize {
+        source: serde_json::Error,
+        filepath: PathBuf,
+    },
+
+    #[snafu(display("Failed to read {filepath:?}"))]
+    Zip {
+        source: zip::result::ZipError,
+        filepath: PathBuf,
+    },
+
+    #[snafu(display("Failed to deserialize {name} in {filepath:?}"))]
+    DeserializeEntry {
+        source: serde_json::Error,
+        name: String,
+        filepath: PathBuf,
+    },
+}
+
+/// Company metadata and filing history from `submissions/CIK##########.json`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanySubmissions {
+    #[serde(deserialize_with = "deserialize_cik")]
+    pub cik: usize,
+    pub name: String,
+    /// e.g. `operating` or `other`
+    #[serde(default, deserialize_with = "non_blank")]
+    pub entity_type: Option<String>,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub sic: Option<String>,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub sic_description: Option<String>,
+    #[serde(default)]
+    pub tickers: Vec<String>,
+    #[serde(default)]
+    pub exchanges: Vec<Option<String>>,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub ein: Option<String>,
+    /// Filer status, e.g. `Large accelerated filer`
+    #[serde(default, deserialize_with = "non_blank")]
+    pub category: Option<String>,
+    #[serde(default, deserialize_with = "fiscal_year_end")]
+    pub fiscal_year_end: Option<FiscalYearEnd>,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub state_of_incorporation: Option<String>,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub state_of_incorporation_description: Option<String>,
+    #[serde(default)]
+    pub addresses: Addresses,
+    #[serde(default, deserialize_with = "non_blank")]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub former_names: Vec<FormerName>,
+    pub filings: Filings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Addresses {
+    pub mailing: Option<Address>,
+    pub business: Option<Address>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    #[serde(default)]
+    pub street1: Option<String>,
+    #[serde(default)]
+    pub street2: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// US state or country code
+    #[serde(default)]
+    pub state_or_country: Option<String>,
+    #[serde(default)]
+    pub state_or_country_description: Option<String>,
+    #[serde(default)]
+    pub zip_code: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FormerName {
+    pub name: String,
+    #[serde(default, deserialize_with = "datetime_date")]
+    pub from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "datetime_date")]
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Filings {
+    /// At least the last year of filings, or the last 1000
+    pub recent: FilingColumns,
+    /// Continuation files with older filings
+    #[serde(default)]
+    pub files: Vec<FilingsFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilingsFile {
+    pub name: String,
+    pub filing_count: usize,
+    #[serde(deserialize_with = "date::iso_date::deserialize")]
+    pub filing_from: NaiveDate,
+    #[serde(deserialize_with = "date::iso_date::deserialize")]
+    pub filing_to: NaiveDate,
+}
+
+/// Filings as stored in the JSON files, one array per field
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FilingColumns {
+    pub accession_number: Vec<String>,
+    #[serde(deserialize_with = "iso_dates")]
+    pub filing_date: Vec<Option<NaiveDate>>,
+    #[serde(deserialize_with = "iso_dates")]
+    pub report_date: Vec<Option<NaiveDate>>,
+    /// Naive US Eastern time, see `datetimes`
+    #[serde(deserialize_with = "datetimes")]
+    pub acceptance_date_time: Vec<Option<NaiveDateTime>>,
+    pub act: Vec<String>,
+    pub form: Vec<String>,
+    pub file_number: Vec<String>,
+    pub film_number: Vec<String>,
+    pub items: Vec<String>,
+    pub size: Vec<u64>,
+    #[serde(rename = "isXBRL")]
+    pub is_xbrl: Vec<u8>,
+    #[serde(rename = "isInlineXBRL")]
+    pub is_inline_xbrl: Vec<u8>,
+    pub primary_document: Vec<String>,
+    pub primary_doc_description: Vec<String>,
+}
+
+/// One row of `FilingColumns`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filing {
+    /// Same format as `FsSub::adsh`, e.g. `0000320193-24-000123`
+    pub accession_number: String,
+    pub filing_date: NaiveDate,
+    pub report_date: Option<NaiveDate>,
+    /// Naive US Eastern time, comparable with `FsSub::accepted`
+    pub acceptance_date_time: Option<NaiveDateTime>,
+    pub act: String,
+    pub form: String,
+    pub file_number: String,
+    pub film_number: String,
+    /// Comma separated 8-K items, e.g. `2.02,9.01`
+    pub items: String,
+    pub size: u64,
+    pub is_xbrl: bool,
+    pub is_inline_xbrl: bool,
+    pub primary_document: String,
+    pub primary_doc_description: String,
+}
+
+impl CompanySubmissions {
+    pub fn new(download_config: &DownloadConfig, cik: usize) -> Result<Self, SubmissionsError> {
+        let data_source =
+            SubmissionsDataSources::company(download_config, cik).context(DataSourceSnafu)?;
+
+        Self::from_path(&data_source.filepath)
+    }
+
+    pub fn from_path(filepath: &Path) -> Result<Self, SubmissionsError> {
+        let reader = BufReader::new(File::open(filepath)?);
+
+        serde_json::from_reader(reader).context(DeserializeSnafu { filepath })
+    }
+
+    pub fn recent_filings(&self) -> impl Iterator<Item = Filing> + '_ {
+        self.filings.recent.iter()
+    }
+
+    /// Recent filings followed by the older filings of every continuation file, which are
+    /// downloaded as needed
+    pub fn all_filings(
+        &self,
+        download_config: &DownloadConfig,
+    ) -> Result<Vec<Filing>, SubmissionsError> {
+        let mut filings = self.recent_filings().collect::<Vec<_>>();
+        for file in &self.filings.files {
+            let data_source = SubmissionsDataSources::file(download_config, &file.name)
+                .context(DataSourceSnafu)?;
+            filings.extend(FilingColumns::from_path(&data_source.filepath)?.iter());
+        }
+
+        Ok(filings)
+    }
+}
+
+impl FilingColumns {
+    /// Reads a continuation file, e.g. `CIK0000320193-submissions-001.json`
+    pub fn from_path(filepath: &Path) -> Result<Self, SubmissionsError> {
+        let reader = BufReader::new(File::open(filepath)?);
+
+        serde_json::from_reader(reader).context(DeserializeSnafu { filepath })
+    }
+
+    pub fn len(&self) -> usize {
+        self.accession_number.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accession_number.is_empty()
+    }
+
+    /// Rows with a filing date. Fields missing from shorter columns are left empty.
+    pub fn iter(&self) -> impl Iterator<Item = Filing> + '_ {
+        (0..self.len()).filter_map(|i| {
+            let text = |column: &[String]| column.get(i).cloned().unwrap_or_default();
+            let flag = |column: &[u8]| column.get(i).is_some_and(|v| *v != 0);
+
+            Some(Filing {
+                accession_number: text(&self.accession_number),
+                filing_date: self.filing_date.get(i).copied().flatten()?,
+                report_date: self.report_date.get(i).copied().flatten(),
+                acceptance_date_time: self.acceptance_date_time.get(i).copied().flatten(),
+                act: text(&self.act),
+                form: text(&self.form),
+                file_number: text(&self.file_number),
+                film_number: text(&self.film_number),
+                items: text(&self.items),
+                size: self.size.get(i).copied().unwrap_or_default(),
+                is_xbrl: flag(&self.is_xbrl),
+                is_inline_xbrl: flag(&self.is_inline_xbrl),
+                primary_document: text(&self.primary_document),
+                primary_doc_description: text(&self.primary_doc_description),
+            })
+        })
+    }
+}
+
+/// The CIK is a string in company files, with or without leading zeros
+fn deserialize_cik<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Cik {
+        Number(usize),
+        Text(String),
+    }
+
+    match Cik::deserialize(deserializer)? {
+        Cik::Number(cik) => Ok(cik),
+        Cik::Text(cik) => cik
+            .trim()
+            .parse()
+            .map_err(|e| de::Error::custom(format!("invalid CIK {cik:?}: {e}"))),
+    }
+}
+
+fn non_blank<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+
+    Ok(value.filter(|v| !v.trim().is_empty()))
+}
+
+fn fiscal_year_end<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<FiscalYearEnd>, D::Error> {
+    non_blank(deserializer)?
+        .map(|v| FiscalYearEnd::deserialize(de::value::StringDeserializer::new(v)))
+        .transpose()
+}
+
+fn parse_iso_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Date part of an ISO 8601 datetime such as `2007-01-10T05:00:00.000Z`
+fn datetime_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    Ok(non_blank(deserializer)?.and_then(|v| parse_iso_date(&v)))
+}
+
+/// `yyyy-mm-dd` dates, blank when unknown
+fn iso_dates<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<NaiveDate>>, D::Error> {
+    let values = Vec::<String>::deserialize(deserializer)?;
+
+    Ok(values.iter().map(|v| parse_iso_date(v)).collect())
+}
+
+/// Datetimes such as `2024-11-01T06:01:36.000Z`, kept naive in US Eastern time.
+///
+/// Despite the `Z` suffix, EDGAR writes acceptance times in Eastern time, the same clock as
+/// `FsSub::accepted`, so the suffix is ignored rather than read as UTC.
+fn datetimes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Option<NaiveDateTime>>, D::Error> {
+    let values = Vec::<String>::deserialize(deserializer)?;
+
+    Ok(values
+        .iter()
+        .map(|v| {
+            let v = v.trim().trim_end_matches('Z');
+            NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S%.f").ok()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const COMPANY_JSON: &str = r#"{
+        "cik": "320193",
+        "entityType": "operating",
+        "sic": "3571",
+        "sicDescription": "Electronic Computers",
+        "name": "Apple Inc.",
+        "tickers": ["AAPL"],
+        "exchanges": ["Nasdaq"],
+        "ein": "942404110",
+        "category": "Large accelerated filer",
+        "fiscalYearEnd": "0928",
+        "stateOfIncorporation": "CA",
+        "stateOfIncorporationDescription": "CA",
+        "addresses": {
+            "mailing": {"street1": "ONE APPLE PARK WAY", "street2": null, "city": "CUPERTINO", "stateOrCountry": "CA", "zipCode": "95014", "stateOrCountryDescription": "CA"},
+            "business": {"street1": "ONE APPLE PARK WAY", "street2": null, "city": "CUPERTINO", "stateOrCountry": "CA", "zipCode": "95014", "stateOrCountryDescription": "CA"}
+        },
+        "phone": "(408) 996-1010",
+        "formerNames": [
+            {"name": "APPLE INC", "from": "2007-01-10T05:00:00.000Z", "to": "2019-08-05T04:00:00.000Z"},
+            {"name": "APPLE COMPUTER INC", "from": "1994-01-26T05:00:00.000Z", "to": "2007-01-04T05:00:00.000Z"}
+        ],
+        "filings": {
+            "recent": {
+                "accessionNumber": ["0000320193-24-000123", "0001140361-24-045910"],
+                "filingDate": ["2024-11-01", "2024-10-31"],
+                "reportDate": ["2024-09-28", ""],
+                "acceptanceDateTime": ["2024-11-01T06:01:36.000Z", "2024-10-31T18:30:21.000Z"],
+                "act": ["34", ""],
+                "form": ["10-K", "4"],
+                "fileNumber": ["001-36743", ""],
+                "filmNumber": ["241416806", ""],
+                "items": ["", ""],
+                "size": [9764467, 5432],
+                "isXBRL": [1, 0],
+                "isInlineXBRL": [1, 0],
+                "primaryDocument": ["aapl-20240928.htm", "xslF345X05/wk-form4.xml"],
+                "primaryDocDescription": ["10-K", "FORM 4"]
+            },
+            "files": [
+                {"name": "CIK0000320193-submissions-001.json", "filingCount": 1, "filingFrom": "1994-01-26", "filingTo": "2014-10-30"}
+            ]
+        }
+    }"#;
+
+    pub(crate) const CONTINUATION_JSON: &str = r#"{
+        "accessionNumber": ["0001193125-14-383437"],
+        "filingDate": ["2014-10-27"],
+        "reportDate": ["2014-09-27"],
+        "acceptanceDateTime": ["2014-10-27T16:32:49.000Z"],
+        "form": ["10-K"],
+        "size": [3563721],
+        "isXBRL": [1],
+        "isInlineXBRL": [0]
+    }"#;
+
+    #[test]
+    fn it_parses_company_submissions() -> Result<(), SubmissionsError> {
+        let dir = std::env::temp_dir();
+        let filepath = dir.join("secparser_CIK0000320193.json");
+        std::fs::write(&filepath, COMPANY_JSON)?;
+
+        let company = CompanySubmissions::from_path(&filepath)?;
+        assert_eq!(company.cik, 320193);
+        assert_eq!(company.sic.as_deref(), Some("3571"));
+        assert_eq!(company.fiscal_year_end, FiscalYearEnd::new(9, 28));
+        assert_eq!(company.state_of_incorporation.as_deref(), Some("CA"));
+        assert_eq!(
+            company.addresses.business.as_ref().unwrap().city.as_deref(),
+            Some("CUPERTINO")
+        );
+        assert_eq!(company.former_names[1].name, "APPLE COMPUTER INC");
+        assert_eq!(
+            company.former_names[1].to,
+            NaiveDate::from_ymd_opt(2007, 1, 4)
+        );
+
+        let filings = company.recent_filings().collect::<Vec<_>>();
+        assert_eq!(filings.len(), 2);
+        assert_eq!(filings[0].form, "10-K");
+        assert_eq!(filings[0].report_date, NaiveDate::from_ymd_opt(2024, 9, 28));
+        assert!(filings[0].is_inline_xbrl);
+        assert_eq!(
+            filings[1].acceptance_date_time,
+            NaiveDate::from_ymd_opt(2024, 10, 31).and_then(|d| d.and_hms_opt(18, 30, 21))
+        );
+        assert_eq!(filings[1].report_date, None);
+        assert_eq!(company.filings.files[0].filing_count, 1);
+
+        let filepath = dir.join("secparser_CIK0000320193-submissions-001.json");
+        std::fs::write(&filepath, CONTINUATION_JSON)?;
+        let older = FilingColumns::from_path(&filepath)?
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(older.len(), 1);
+        assert_eq!(older[0].act, "");
+        assert!(!older[0].is_inline_xbrl);
+
+        Ok(())
+    }
+}
@@ -1,12 +1,13 @@
 use aliasable::boxed::AliasableBox;
-use csv::{DeserializeRecordsIntoIter, ReaderBuilder};
+use csv::{ByteRecord, Reader, ReaderBuilder};
 use derive_builder::Builder;
 use serde::de::DeserializeOwned;
 use snafu::{Location, Snafu};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io;
 use std::io::BufReader;
-use std::iter::FilterMap;
+use std::marker::PhantomData;
 use zip::read::ZipFile;
 use zip::ZipArchive;
 
@@ -29,6 +30,17 @@ pub enum ZipCsvRecordsError {
         #[snafu(implicit)]
         loc: Location,
     },
+
+    #[snafu(display("Csv error at {loc}"))]
+    #[snafu(context(false))]
+    Csv {
+        source: csv::Error,
+        #[snafu(implicit)]
+        loc: Location,
+    },
+
+    #[snafu(display("Cannot filter on {column}, not a column of {csv_file}"))]
+    FilterColumn { column: String, csv_file: String },
 }
 
 #[derive(Clone, Debug, Builder)]
@@ -41,16 +53,97 @@ pub struct CsvConfig {
     pub panic_on_error: bool,
 }
 
-type FilterMapRecord<T> = fn(record: Result<T, csv::Error>) -> Option<T>;
-type RecordIter<'archive, T> =
-    FilterMap<DeserializeRecordsIntoIter<BufReader<ZipFile<'archive>>, T>, FilterMapRecord<T>>;
+/// Keeps only rows whose raw column values are in the allowed sets, checked before
+/// deserialization.
+///
+/// Columns added with `column` must be in the csv header, otherwise opening the records fails
+/// with `ZipCsvRecordsError::FilterColumn`. Columns added with `optional_column` are skipped
+/// for files without them, e.g. a `tag` filter shared by `num.tsv` and `ren.tsv`.
+#[derive(Clone, Debug, Default)]
+pub struct RecordFilter {
+    columns: Vec<ColumnFilter>,
+}
+
+/// Column index and allowed values
+type ResolvedFilter = (usize, HashSet<Vec<u8>>);
+
+#[derive(Clone, Debug)]
+struct ColumnFilter {
+    name: String,
+    values: HashSet<Vec<u8>>,
+    required: bool,
+}
+
+impl RecordFilter {
+    pub fn column<I, V>(self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Vec<u8>>,
+    {
+        self.push(name, values, true)
+    }
+
+    pub fn optional_column<I, V>(self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Vec<u8>>,
+    {
+        self.push(name, values, false)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    fn push<I, V>(mut self, name: &str, values: I, required: bool) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Vec<u8>>,
+    {
+        self.columns.push(ColumnFilter {
+            name: name.to_string(),
+            values: values.into_iter().map(Into::into).collect(),
+            required,
+        });
+        self
+    }
+
+    fn resolve(
+        &self,
+        headers: &ByteRecord,
+        csv_file: &str,
+    ) -> Result<Vec<ResolvedFilter>, ZipCsvRecordsError> {
+        let mut result = vec![];
+        for column in &self.columns {
+            let index = headers.iter().position(|h| h == column.name.as_bytes());
+            match index {
+                Some(i) => result.push((i, column.values.clone())),
+                None if column.required => {
+                    return FilterColumnSnafu {
+                        column: &column.name,
+                        csv_file,
+                    }
+                    .fail()
+                }
+                None => log::debug!("Skip filter on {}, not a column of {csv_file}", column.name),
+            }
+        }
+
+        Ok(result)
+    }
+}
 
 pub struct ZipCsvRecords<T>
 where
     T: DeserializeOwned,
 {
-    record_iter: RecordIter<'static, T>,
+    reader: Reader<BufReader<ZipFile<'static>>>,
+    headers: ByteRecord,
+    record: ByteRecord,
+    filter: Vec<ResolvedFilter>,
+    panic_on_error: bool,
     _archive: AliasableBox<ZipArchive<File>>,
+    _record_type: PhantomData<T>,
 }
 
 impl<T> ZipCsvRecords<T>
@@ -61,6 +154,15 @@ where
         data_source: &DataSource,
         config: &CsvConfig,
         csv_file: &str,
+    ) -> Result<Self, ZipCsvRecordsError> {
+        Self::new_with_filter(data_source, config, csv_file, &RecordFilter::default())
+    }
+
+    pub fn new_with_filter(
+        data_source: &DataSource,
+        config: &CsvConfig,
+        csv_file: &str,
+        filter: &RecordFilter,
     ) -> Result<Self, ZipCsvRecordsError> {
         let zip_file = File::open(&data_source.filepath)?;
         let archive = ZipArchive::new(zip_file)?;
@@ -68,29 +170,39 @@ where
 
         let file = archive.by_name(csv_file)?;
         let reader = BufReader::new(file);
-        let reader = ReaderBuilder::new()
+        let mut reader = ReaderBuilder::new()
             .quoting(config.csv_quoting)
             .flexible(config.csv_flexible)
             .delimiter(b'\t')
             .from_reader(reader);
+        let headers = reader.byte_headers()?.clone();
+        let filter = filter.resolve(&headers, csv_file)?;
 
-        fn panic_on_error<T>(maybe_record: Result<T, csv::Error>) -> Option<T> {
-            Some(maybe_record.unwrap_or_else(|e| panic!("Should parse {e}")))
-        }
-        fn ignore_error<T>(maybe_record: Result<T, csv::Error>) -> Option<T> {
-            maybe_record.ok()
-        }
-        let record_iter: RecordIter<T> = match config.panic_on_error {
-            true => reader.into_deserialize().filter_map(panic_on_error),
-            false => reader.into_deserialize().filter_map(ignore_error),
-        };
-        let record_iter: RecordIter<T> = unsafe { std::mem::transmute(record_iter) };
+        let reader: Reader<BufReader<ZipFile<'static>>> = unsafe { std::mem::transmute(reader) };
 
         Ok(Self {
-            record_iter,
+            reader,
+            headers,
+            record: ByteRecord::new(),
+            filter,
+            panic_on_error: config.panic_on_error,
             _archive: archive,
+            _record_type: PhantomData,
         })
     }
+
+    fn is_match(&self) -> bool {
+        self.filter
+            .iter()
+            .all(|(i, values)| self.record.get(*i).is_some_and(|v| values.contains(v)))
+    }
+
+    fn on_error(&self, e: csv::Error) {
+        match self.panic_on_error {
+            true => panic!("Should parse {e}"),
+            false => log::debug!("Skip record: {e}"),
+        }
+    }
 }
 
 impl<T> Iterator for ZipCsvRecords<T>
@@ -100,6 +212,90 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.record_iter.next()
+        loop {
+            match self.reader.read_byte_record(&mut self.record) {
+                Ok(false) => return None,
+                Ok(true) if !self.is_match() => continue,
+                Ok(true) => match self.record.deserialize(Some(&self.headers)) {
+                    Ok(v) => return Some(v),
+                    Err(e) => self.on_error(e),
+                },
+                Err(e) => self.on_error(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn write_test_zip(name: &str, files: &[(&str, &str)]) -> DataSource {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    let filepath = std::env::temp_dir().join(name);
+    let mut writer = ZipWriter::new(File::create(&filepath).unwrap());
+    for (csv_file, content) in files {
+        writer
+            .start_file(*csv_file, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+
+    DataSource { filepath }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Row {
+        adsh: String,
+        value: u32,
+    }
+
+    #[test]
+    fn it_filters_raw_records() -> Result<(), ZipCsvRecordsError> {
+        let data_source = write_test_zip(
+            "secparser_zip_csv_records_filter.zip",
+            &[(
+                "num.tsv",
+                "adsh\ttag\tvalue\na\tRevenues\t1\nb\tRevenues\t2\na\tAssets\tnot-a-number\nc\tAssets\t3\n",
+            )],
+        );
+        let config = CsvConfigBuilder::default().build().unwrap();
+
+        let all: Vec<Row> = ZipCsvRecords::new(&data_source, &config, "num.tsv")?.collect();
+        assert_eq!(all.len(), 3);
+
+        let filter = RecordFilter::default()
+            .column("adsh", ["a", "c"])
+            .optional_column("missing", ["x"]);
+        let filtered: Vec<Row> =
+            ZipCsvRecords::new_with_filter(&data_source, &config, "num.tsv", &filter)?.collect();
+        assert_eq!(
+            filtered,
+            vec![
+                Row {
+                    adsh: "a".to_string(),
+                    value: 1
+                },
+                Row {
+                    adsh: "c".to_string(),
+                    value: 3
+                }
+            ]
+        );
+
+        let filter = RecordFilter::default().column("missing", ["x"]);
+        assert!(matches!(
+            ZipCsvRecords::<Row>::new_with_filter(&data_source, &config, "num.tsv", &filter),
+            Err(ZipCsvRecordsError::FilterColumn { .. })
+        ));
+
+        Ok(())
     }
 }
@@ -1,5 +1,8 @@
 use secparser_core::{
-    cik_lookup::record::{CikLookup, CikLookupRecords},
+    cik_lookup::{
+        record::{CikLookup, CikLookupRecords},
+        tickers::{self, FundTicker, Listing},
+    },
     downloader::DownloadConfigBuilder,
 };
 use snafu::{ResultExt, Whatever};
@@ -12,9 +15,31 @@ impl IngestibleRecord for CikLookup {
     }
 }
 
+/// CIK lookup records skipping lines that fail to parse, so one malformed line does not abort
+/// the ingestion
+pub struct ValidCikLookupRecords(CikLookupRecords);
+
+impl Iterator for ValidCikLookupRecords {
+    type Item = CikLookup;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.0.next()? {
+                Ok(record) => return Some(record),
+                Err(e) => log::warn!("Skipping CIK lookup record: {e}"),
+            }
+        }
+    }
+
+    /// Bounded by the remaining lookup lines, some of which may be skipped
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.0.size_hint().1)
+    }
+}
+
 impl IngestableRecordIter for CikLookupRecords {
     type Item = CikLookup;
-    type IntoIter = CikLookupRecords;
+    type IntoIter = ValidCikLookupRecords;
 
     fn get() -> Result<Self::IntoIter, Whatever> {
         let user_agent = "example@secparser.com".to_string();
@@ -23,7 +48,10 @@ impl IngestableRecordIter for CikLookupRecords {
             .build()
             .whatever_context("Failed to create download config")?;
 
-        CikLookupRecords::new(&download_config).whatever_context("Failed to get records")
+        let records =
+            CikLookupRecords::new(&download_config).whatever_context("Failed to get records")?;
+
+        Ok(ValidCikLookupRecords(records))
     }
 }
 
@@ -34,3 +62,74 @@ impl IngestibleRecordTable for CikLookupTable {
         String::from("cik_lookup")
     }
 }
+
+impl IngestibleRecord for Listing {
+    fn display_name(&self) -> String {
+        self.ticker.to_string()
+    }
+}
+
+pub struct ListingRecords {}
+
+impl IngestableRecordIter for ListingRecords {
+    type Item = Listing;
+    type IntoIter = std::vec::IntoIter<Listing>;
+
+    fn get() -> Result<Self::IntoIter, Whatever> {
+        let user_agent = "example@secparser.com".to_string();
+        let download_config = DownloadConfigBuilder::default()
+            .user_agent(user_agent)
+            .build()
+            .whatever_context("Failed to create download config")?;
+
+        let records =
+            CikLookupRecords::new(&download_config).whatever_context("Failed to get records")?;
+
+        Ok(records.into_listings().into_iter())
+    }
+}
+
+pub struct ListingTable {}
+
+impl IngestibleRecordTable for ListingTable {
+    fn table_name() -> String {
+        String::from("cik_listing")
+    }
+}
+
+impl IngestibleRecord for FundTicker {
+    fn display_name(&self) -> String {
+        match self.symbol.is_empty() {
+            true => self.class_id.to_string(),
+            false => self.symbol.to_string(),
+        }
+    }
+}
+
+pub struct FundTickerRecords {}
+
+impl IngestableRecordIter for FundTickerRecords {
+    type Item = FundTicker;
+    type IntoIter = std::vec::IntoIter<FundTicker>;
+
+    fn get() -> Result<Self::IntoIter, Whatever> {
+        let user_agent = "example@secparser.com".to_string();
+        let download_config = DownloadConfigBuilder::default()
+            .user_agent(user_agent)
+            .build()
+            .whatever_context("Failed to create download config")?;
+
+        let funds = tickers::fund_tickers(&download_config)
+            .whatever_context("Failed to get fund tickers")?;
+
+        Ok(funds.into_iter())
+    }
+}
+
+pub struct FundTickerTable {}
+
+impl IngestibleRecordTable for FundTickerTable {
+    fn table_name() -> String {
+        String::from("cik_fund")
+    }
+}
@@ -5,7 +5,9 @@ use snafu::{ResultExt, Whatever};
 
 use crate::ingestible::ingest;
 
-use super::cik_lookup_ingestible::CikLookupTable;
+use super::cik_lookup_ingestible::{
+    CikLookupTable, FundTickerRecords, FundTickerTable, ListingRecords, ListingTable,
+};
 
 pub fn open() -> Result<(), Whatever> {
     let user_agent = "example@secparser.com".to_string();
@@ -42,7 +44,8 @@ pub fn open() -> Result<(), Whatever> {
 
             let maybe_record = records.next();
             match maybe_record {
-                Some(r) => println!("{}", format!("{r:#?}").green()),
+                Some(Ok(r)) => println!("{}", format!("{r:#?}").green()),
+                Some(Err(e)) => println!("{}", format!("{e}").red()),
                 None => println!("End of records"),
             }
 
@@ -52,6 +55,10 @@ pub fn open() -> Result<(), Whatever> {
         if ans == ingest_opt {
             ingest::<CikLookupRecords, CikLookupTable>()
                 .whatever_context("Failed to ingest CIK lookup")?;
+            ingest::<ListingRecords, ListingTable>()
+                .whatever_context("Failed to ingest listings")?;
+            ingest::<FundTickerRecords, FundTickerTable>()
+                .whatever_context("Failed to ingest fund tickers")?;
 
             continue;
         }
@@ -1,5 +1,6 @@
 use colored::Colorize;
 use inquire::Select;
+use secparser_core::financial_statements::codes::FormType;
 use secparser_core::financial_statements::num_record::FsNum;
 use secparser_core::financial_statements::record::FsRecords;
 use secparser_core::financial_statements::sub_record::FsSub;
@@ -40,7 +41,7 @@ pub fn open() -> Result<(), Whatever> {
 
                 match maybe_record {
                     Some(r) => {
-                        if r.form == "10-K" {
+                        if r.form == FormType::TenK {
                             println!("{}", format!("{r:#?}").green());
                             break;
                         }
@@ -51,12 +51,15 @@ where
 
     if !row.get::<&str, bool>("exists") {
         create_table(&mut db, &table_name)?;
+    } else {
+        migrate_table(&mut db, &table_name)?;
     }
 
     let records = I::get()?;
     let csv_path = format!("{}{}.csv", csv_dir, table_name);
 
-    let bar = CustomProgressBar::new(0);
+    let (lower, upper) = records.size_hint();
+    let bar = CustomProgressBar::new(upper.unwrap_or(lower));
     for chunk in &records.chunks(chunk_size) {
         let mut writer =
             Writer::from_path(&csv_path).whatever_context("Failed to get csv writer")?;
@@ -78,6 +81,69 @@ where
     Ok(())
 }
 
+/// Upgrades tables created by earlier versions to the schema of `create_table`
+fn migrate_table(db: &mut PostgresDb, table_name: &str) -> Result<(), Whatever> {
+    if table_name == "cik_lookup" {
+        // Listings moved to cik_listing, and cik_lookup now keeps every name of a CIK
+        let query = "
+        SELECT EXISTS (
+        SELECT FROM information_schema.columns
+        WHERE  table_schema = 'public'
+        AND    table_name   = 'cik_lookup'
+        AND    column_name  = 'ticker'
+        );
+        ";
+        let row = db
+            .client
+            .query_one(query, &[])
+            .whatever_context("Failed to check cik_lookup columns")?;
+
+        if row.get::<&str, bool>("exists") {
+            log::info!("Migrating cik_lookup: dropping ticker and exchange, now in cik_listing");
+            db.client
+                .batch_execute(
+                    "
+                    BEGIN;
+                    ALTER TABLE cik_lookup
+                      DROP COLUMN ticker,
+                      DROP COLUMN IF EXISTS exchange,
+                      DROP CONSTRAINT IF EXISTS cik_lookup_pkey;
+                    DELETE FROM cik_lookup WHERE name IS NULL;
+                    ALTER TABLE cik_lookup
+                      ALTER COLUMN name SET NOT NULL,
+                      ADD PRIMARY KEY (cik, name);
+                    COMMIT;
+                    ",
+                )
+                .whatever_context("Failed to migrate cik_lookup")?;
+        }
+    } else if table_name == "fs_num" {
+        // REAL kept only about 7 significant digits of values
+        let query = "
+        SELECT EXISTS (
+        SELECT FROM information_schema.columns
+        WHERE  table_schema = 'public'
+        AND    table_name   = 'fs_num'
+        AND    column_name  = 'value'
+        AND    data_type    = 'real'
+        );
+        ";
+        let row = db
+            .client
+            .query_one(query, &[])
+            .whatever_context("Failed to check fs_num columns")?;
+
+        if row.get::<&str, bool>("exists") {
+            log::info!("Migrating fs_num: widening value to DOUBLE PRECISION");
+            db.client
+                .batch_execute("ALTER TABLE fs_num ALTER COLUMN value TYPE DOUBLE PRECISION;")
+                .whatever_context("Failed to migrate fs_num")?;
+        }
+    }
+
+    Ok(())
+}
+
 pub fn copy_from_csv(
     db: &mut PostgresDb,
     csv_path: &str,
@@ -111,11 +177,32 @@ fn create_table(db: &mut PostgresDb, table_name: &str) -> Result<(), Whatever> {
         CREATE TABLE cik_lookup
         (
           cik OID NOT NULL,
-          name TEXT,
-          ticker TEXT,
+          name TEXT NOT NULL,
+
+          PRIMARY KEY (cik, name)
+        );
+        "
+    } else if table_name == "cik_listing" {
+        "
+        CREATE TABLE cik_listing
+        (
+          cik OID NOT NULL,
+          ticker TEXT NOT NULL,
           exchange TEXT,
 
-          PRIMARY KEY (cik)
+          PRIMARY KEY (cik, ticker)
+        );
+        "
+    } else if table_name == "cik_fund" {
+        "
+        CREATE TABLE cik_fund
+        (
+          cik OID NOT NULL,
+          series_id TEXT,
+          class_id TEXT NOT NULL,
+          symbol TEXT,
+
+          PRIMARY KEY (cik, class_id)
         );
         "
     } else if table_name == "fs_sub" {
@@ -189,7 +276,7 @@ fn create_table(db: &mut PostgresDb, table_name: &str) -> Result<(), Whatever> {
           uom TEXT NOT NULL,
           -- dimh TEXT NOT NULL,
           -- iprx SMALLINT NOT NULL,
-          value REAL,
+          value DOUBLE PRECISION,
           -- footnote TEXT,
           -- footlen BIGINT,
           -- dimn SMALLINT,
@@ -222,7 +309,7 @@ fn create_table(db: &mut PostgresDb, table_name: &str) -> Result<(), Whatever> {
         );
         "
     } else {
-        whatever!("Should create one of the tables: cik_lookup, fs_sub, fs_num, fs_tag");
+        whatever!("Should create one of the tables: cik_lookup, cik_listing, cik_fund, fs_sub, fs_num, fs_tag");
     };
 
     db.client